@@ -1,5 +1,15 @@
-use windres::Build;
+use embed_resource::CompilationResult;
 
 fn main() {
-    Build::new().compile("icons.rc").unwrap();
-}
\ No newline at end of file
+    // `#[cfg(windows)]` would test the host running this script, not the target being built
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("windows") {
+        return;
+    }
+
+    match embed_resource::compile("icons.rc", embed_resource::NONE) {
+        CompilationResult::Ok | CompilationResult::NotWindows => {}
+        // Cross-compiling without a resource compiler still builds, just without icons
+        CompilationResult::NotAttempted(why) => println!("cargo:warning=Icons were not embedded: {}", why),
+        CompilationResult::Failed(why) => panic!("Failed to compile icons.rc: {}", why),
+    }
+}
@@ -0,0 +1,47 @@
+use std::{env, fs::File, io::{BufReader, BufWriter, Write}, path::PathBuf};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use anyhow::{Context, Result};
+
+use crate::games::Game;
+
+/// The last offer list fetched while online, shown until the next successful fetch
+/// so that starting without network doesn't leave the tray empty.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OfferCache {
+    pub fetched_at: DateTime<Utc>,
+    pub games: Vec<Game>,
+}
+
+impl OfferCache {
+    pub fn load() -> Option<Self> {
+        let file = File::open(Self::get_cache_path()).ok()?;
+        let reader = BufReader::new(file);
+
+        match serde_json::from_reader(reader) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                tracing::error!("Ignoring broken offer cache: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn get_cache_path() -> PathBuf {
+        let mut exe_path = env::current_exe().unwrap();
+        exe_path.set_file_name("offers");
+        exe_path.set_extension("json");
+        exe_path
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::get_cache_path();
+        let file = File::create(&path).context("Failed to create offer cache")?;
+        let mut writer = BufWriter::new(file);
+
+        let json = serde_json::to_string(self).context("Failed to serialize offer cache")?;
+        writer.write_all(json.as_bytes()).context("Failed to write offer cache")?;
+
+        Ok(())
+    }
+}
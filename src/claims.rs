@@ -0,0 +1,188 @@
+use std::{env, fs::File, io::{BufReader, BufWriter, Write}, path::PathBuf, sync::Arc};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use anyhow::{Context, Result};
+
+use crate::{games::Game, platform::Platform};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaimAction {
+    #[default]
+    Claimed,
+    Unclaimed,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClaimRecord {
+    pub id: usize,
+    pub title: String,
+    pub platform: Platform,
+    /// Older files only hold claims, so a missing action means claimed.
+    #[serde(default)]
+    pub action: ClaimAction,
+    #[serde(alias = "claimed_at")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Offers marked as claimed. Records are only ever appended, unclaiming adds
+/// its own record, so the file doubles as the history of everything grabbed so far.
+#[derive(Clone)]
+pub struct Claims {
+    records: Arc<Mutex<Vec<ClaimRecord>>>,
+}
+
+impl Claims {
+    pub fn new() -> Self {
+        Claims {
+            records: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub async fn is_claimed(&self, id: usize) -> bool {
+        Self::last_record(&self.records.lock().await, id)
+            .is_some_and(|r| r.action == ClaimAction::Claimed)
+    }
+
+    pub async fn mark_claimed(&self, game: &Game) {
+        let mut records = self.records.lock().await;
+
+        if Self::last_record(&records, game.id).is_some_and(|r| r.action == ClaimAction::Claimed) {
+            return;
+        }
+
+        records.push(ClaimRecord {
+            id: game.id,
+            title: game.title.clone(),
+            platform: game.platform,
+            action: ClaimAction::Claimed,
+            recorded_at: Utc::now(),
+        });
+    }
+
+    pub async fn unmark_claimed(&self, id: usize) {
+        let mut records = self.records.lock().await;
+
+        let Some(claim) = Self::last_record(&records, id).filter(|r| r.action == ClaimAction::Claimed) else {
+            return;
+        };
+
+        let unclaim = ClaimRecord {
+            action: ClaimAction::Unclaimed,
+            recorded_at: Utc::now(),
+            ..claim.clone()
+        };
+
+        records.push(unclaim);
+    }
+
+    /// The latest record decides, an offer can be claimed and unclaimed any number of times.
+    fn last_record(records: &[ClaimRecord], id: usize) -> Option<&ClaimRecord> {
+        records.iter().rev().find(|r| r.id == id)
+    }
+
+    pub async fn load_or_init(&self) -> Result<()> {
+        let path = Self::get_claims_path();
+
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let file = File::open(&path).context("Failed to open claimed offers")?;
+        let reader = BufReader::new(file);
+        let records: Vec<ClaimRecord> = serde_json::from_reader(reader).unwrap_or_default();
+
+        let mut current = self.records.lock().await;
+        *current = records;
+
+        Ok(())
+    }
+
+    pub fn get_claims_path() -> PathBuf {
+        let mut exe_path = env::current_exe().unwrap();
+        exe_path.set_file_name("claimed");
+        exe_path.set_extension("json");
+        exe_path
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        let path = Self::get_claims_path();
+        let file = File::create(&path).context("Failed to create claimed offers file")?;
+        let mut writer = BufWriter::new(file);
+
+        let records = self.records.lock().await.clone();
+        let json = serde_json::to_string_pretty(&records).context("Failed to serialize claimed offers")?;
+        writer.write_all(json.as_bytes()).context("Failed to write claimed offers")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(id: usize) -> Game {
+        Game {
+            id,
+            title: "Hollow Lantern".to_string(),
+            platform: Platform::Epic,
+            ..Game::default()
+        }
+    }
+
+    async fn actions(claims: &Claims) -> Vec<ClaimAction> {
+        claims.records.lock().await.iter().map(|r| r.action).collect()
+    }
+
+    #[tokio::test]
+    async fn keeps_the_whole_claim_history() {
+        let claims = Claims::new();
+
+        claims.mark_claimed(&game(1)).await;
+        assert!(claims.is_claimed(1).await);
+
+        claims.unmark_claimed(1).await;
+        assert!(!claims.is_claimed(1).await);
+
+        claims.mark_claimed(&game(1)).await;
+        assert!(claims.is_claimed(1).await);
+        assert!(!claims.is_claimed(2).await);
+
+        assert_eq!(actions(&claims).await, [ClaimAction::Claimed, ClaimAction::Unclaimed, ClaimAction::Claimed]);
+    }
+
+    #[tokio::test]
+    async fn ignores_repeated_marks() {
+        let claims = Claims::new();
+
+        claims.unmark_claimed(1).await;
+        claims.mark_claimed(&game(1)).await;
+        claims.mark_claimed(&game(1)).await;
+        claims.unmark_claimed(1).await;
+        claims.unmark_claimed(1).await;
+
+        assert_eq!(actions(&claims).await, [ClaimAction::Claimed, ClaimAction::Unclaimed]);
+    }
+
+    #[tokio::test]
+    async fn reads_files_from_before_unclaiming() {
+        let records: Vec<ClaimRecord> = serde_json::from_str(r#"[
+            { "id": 1, "title": "Hollow Lantern", "platform": "epic", "claimed_at": "2025-03-04T10:00:00Z" }
+        ]"#).unwrap();
+
+        assert_eq!(records[0].action, ClaimAction::Claimed);
+        assert_eq!(records[0].recorded_at, "2025-03-04T10:00:00Z".parse::<DateTime<Utc>>().unwrap());
+
+        let claims = Claims::new();
+        *claims.records.lock().await = records;
+
+        assert!(claims.is_claimed(1).await);
+
+        // New records are written under the new name
+        let json = serde_json::to_value(&*claims.records.lock().await).unwrap();
+        assert_eq!(json[0]["recorded_at"], "2025-03-04T10:00:00Z");
+        assert_eq!(json[0]["action"], "claimed");
+    }
+}
@@ -0,0 +1,337 @@
+use std::{collections::HashSet, env, fs::File, io::{BufReader, BufWriter, Write}, path::PathBuf, process::ExitCode};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    claims::Claims,
+    config::Config,
+    games::{format_time_left, Games, GiveawayType},
+    http,
+    instance::{self, InstanceLock},
+    notifications::Notifications,
+    platform::Platform,
+    sinks,
+    tray::Message,
+};
+
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Run the fetch-and-notify loop without any tray
+    #[arg(long)]
+    pub headless: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print currently active offers
+    List {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Fetch offers and send notifications about the new ones
+    Refresh,
+    /// Turn notifications on or off
+    ToggleNotifications,
+    /// Stop the running instance
+    Quit,
+    /// Exit with code 1 when offers showed up since the last run
+    Check,
+    /// Mark an offer as claimed, ids are shown by `list`
+    MarkClaimed {
+        id: usize,
+    },
+    /// Read or change config.toml
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print a setting, e.g. `providers.epic`
+    Get {
+        key: String,
+    },
+    /// Change a setting, e.g. `reminder_before_hours 12`
+    Set {
+        key: String,
+        /// Negative numbers are values too, e.g. Telegram group ids
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+}
+
+#[derive(Serialize)]
+struct OfferRow<'a> {
+    id: usize,
+    title: &'a str,
+    platform: Platform,
+    #[serde(rename = "type")]
+    game_type: GiveawayType,
+    url: &'a str,
+    worth: Option<f64>,
+    end_date: Option<DateTime<Utc>>,
+    claimed: bool,
+    stale_since: Option<DateTime<Utc>>,
+}
+
+/// Offers the previous `check` saw. Kept apart from the notification log, so checking
+/// from cron doesn't swallow the notifications of the tray.
+#[derive(Default, Deserialize, Serialize)]
+struct CheckMarker {
+    seen: HashSet<usize>,
+}
+
+impl CheckMarker {
+    fn load() -> Self {
+        let Ok(file) = File::open(Self::get_marker_path()) else {
+            return CheckMarker::default();
+        };
+
+        serde_json::from_reader(BufReader::new(file)).unwrap_or_default()
+    }
+
+    fn get_marker_path() -> PathBuf {
+        let mut exe_path = env::current_exe().unwrap();
+        exe_path.set_file_name("last_check");
+        exe_path.set_extension("json");
+        exe_path
+    }
+
+    fn save(&self) -> Result<()> {
+        let file = File::create(Self::get_marker_path()).context("Failed to create check marker")?;
+        let mut writer = BufWriter::new(file);
+
+        let json = serde_json::to_string(self).context("Failed to serialize check marker")?;
+        writer.write_all(json.as_bytes()).context("Failed to write check marker")?;
+
+        Ok(())
+    }
+}
+
+/// Attaches to the console of the terminal the app was started from, the tray build has none of its own.
+#[cfg(windows)]
+pub fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn attach_console() {}
+
+pub async fn run(command: Command) -> Result<ExitCode> {
+    let mut config = Config::new();
+    config.load().await;
+
+    if let Command::Config { action } = command {
+        return run_config(&mut config, action).await.map(|()| ExitCode::SUCCESS);
+    }
+
+    http::init(&config.get_network().await)?;
+
+    // A running tray or headless instance owns the state files, so it gets to handle these itself
+    let forwarded = match command {
+        Command::Refresh => Some(Message::Refresh),
+        Command::ToggleNotifications => Some(Message::ToggleNotifications),
+        Command::Quit => Some(Message::Quit),
+        Command::MarkClaimed { id } => Some(Message::MarkClaimed(id)),
+        _ => None,
+    };
+
+    if let Some(message) = &forwarded {
+        if instance::send(message).await? {
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+
+    match command {
+        Command::Quit => bail!("FreeTrayGames is not running"),
+        Command::ToggleNotifications => return toggle_notifications(&mut config).await.map(|()| ExitCode::SUCCESS),
+        _ => {}
+    }
+
+    // Keeps a tray or headless instance from starting and writing the same state files meanwhile
+    let lock = InstanceLock::acquire()?;
+
+    if lock.is_none() && forwarded.is_some() {
+        bail!("FreeTrayGames is starting up, try again in a moment");
+    }
+
+    let games = match &lock {
+        Some(_) => Games::fetch(&config).await.context("Failed to initialize games")?,
+        // The running instance owns the state files and keeps the offer cache fresh, so only read it
+        None => Games::from_cache(&config).await,
+    };
+
+    let claims = Claims::new();
+    claims.load_or_init().await.context("Failed to load claimed offers")?;
+
+    match command {
+        Command::List { json } => list(&games, &claims, json).await,
+        Command::Refresh => refresh(&games, &claims, &config).await,
+        Command::Check => return check(&games, &claims).await,
+        Command::MarkClaimed { id } => mark_claimed(&games, &claims, id).await,
+        Command::Config { .. } | Command::ToggleNotifications | Command::Quit => {
+            unreachable!("handled before fetching offers")
+        }
+    }?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn run_config(config: &mut Config, action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Get { key } => match config.get_value(&key).await? {
+            Some(value) => println!("{}", value),
+            None => println!("{} is not set", key),
+        },
+        ConfigAction::Set { key, value } => {
+            // A running instance would save its own copy of the settings over the change
+            let Some(_lock) = InstanceLock::acquire()? else {
+                bail!("FreeTrayGames is running, quit it before changing settings");
+            };
+
+            // It may have saved between loading and locking
+            config.load().await;
+            config.set_value(&key, &value).await?;
+            config.save().await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn toggle_notifications(config: &mut Config) -> Result<()> {
+    config.toggle_notifications().await;
+    config.save().await?;
+
+    if config.is_notifications_enabled().await {
+        println!("Notifications enabled");
+    } else {
+        println!("Notifications disabled");
+    }
+
+    Ok(())
+}
+
+async fn list(games: &Games, claims: &Claims, json: bool) -> Result<()> {
+    let stale_since = games.get_stale_since().await;
+    let games = games.get_all().await;
+    let mut rows = Vec::new();
+
+    for game in &games {
+        rows.push(OfferRow {
+            id: game.id,
+            title: &game.title,
+            platform: game.platform,
+            game_type: game.game_type,
+            url: game.get_open_url(),
+            worth: game.worth,
+            end_date: game.end_date,
+            claimed: claims.is_claimed(game.id).await,
+            stale_since: game.stale_since,
+        });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows).context("Failed to serialize offers")?);
+        return Ok(());
+    }
+
+    if rows.is_empty() {
+        println!("No active giveaways");
+        return Ok(());
+    }
+
+    let now = Utc::now();
+
+    if let Some(stale_since) = stale_since {
+        println!("Offline, offers below are stale since {}", stale_since.with_timezone(&Local).format("%Y-%m-%d %H:%M"));
+    }
+
+    println!("{:<20}  {:<10}  {:<12}  {:<8}  {:<7}  TITLE", "ID", "PLATFORM", "TYPE", "ENDS IN", "CLAIMED");
+
+    for (row, game) in rows.iter().zip(&games) {
+        let ends_in = game.time_left(now).map(format_time_left).unwrap_or_else(|| "-".to_string());
+
+        println!(
+            "{:<20}  {:<10}  {:<12}  {:<8}  {:<7}  {}",
+            row.id,
+            row.platform.as_str(),
+            row.game_type.label(),
+            ends_in,
+            if row.claimed { "yes" } else { "" },
+            if row.stale_since.is_some() { format!("{} (stale)", row.title) } else { row.title.to_string() },
+        );
+    }
+
+    Ok(())
+}
+
+async fn refresh(games: &Games, claims: &Claims, config: &Config) -> Result<()> {
+    let notifications = Notifications::new(
+        games.clone(),
+        claims.clone(),
+        config.clone(),
+        sinks::enabled_sinks(&config.get_sinks().await, true),
+    );
+    notifications.load_or_init().await.context("Failed to load notifications")?;
+
+    notifications.push_all_new_games().await
+}
+
+/// Prints the offers new since the last check, exiting with 1 when there are any.
+async fn check(games: &Games, claims: &Claims) -> Result<ExitCode> {
+    let previous = CheckMarker::load();
+    let games = games.get_all().await;
+    let mut new_games = Vec::new();
+
+    for game in &games {
+        if game.is_silenced || previous.seen.contains(&game.id) || claims.is_claimed(game.id).await {
+            continue;
+        }
+
+        new_games.push(game);
+    }
+
+    for game in &new_games {
+        println!("{} ({})", game.title, game.get_open_url());
+    }
+
+    let marker = CheckMarker {
+        seen: games.iter().map(|g| g.id).collect(),
+    };
+    marker.save()?;
+
+    if new_games.is_empty() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+async fn mark_claimed(games: &Games, claims: &Claims, id: usize) -> Result<()> {
+    let games = games.get_all().await;
+
+    let game = games.iter()
+        .find(|g| g.id == id)
+        .with_context(|| format!("No active offer with id {}", id))?;
+
+    claims.mark_claimed(game).await;
+    claims.save().await?;
+
+    println!("Marked {} as claimed", game.title);
+
+    Ok(())
+}
@@ -1,126 +1,632 @@
-use std::sync::Arc;
-use reqwest::StatusCode;
-use serde::Deserialize;
+use std::{cmp::Ordering, collections::HashSet, sync::Arc};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tokio::sync::Mutex;
 use anyhow::{Context, Result};
 
-#[derive(Debug, Deserialize, Clone)]
+use crate::cache::OfferCache;
+use crate::config::Config;
+use crate::http::HttpCache;
+use crate::platform::Platform;
+use crate::providers::{enabled_providers, FetchError, GiveawayProvider, ProviderHealth};
+use crate::rules::{RuleSet, Verdict};
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct Game {
     pub id: usize,
     pub title: String,
     pub platforms: String,
     pub image: String,
     #[serde(default)]
-    pub platform: String,
+    pub platform: Platform,
     pub open_giveaway_url: String,
     #[serde(rename = "type")]
-    pub game_type: String,
+    pub game_type: GiveawayType,
     pub status: String,
+    #[serde(default, deserialize_with = "deserialize_worth", serialize_with = "serialize_worth")]
+    pub worth: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub end_date: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub published_date: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub instructions: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub thumbnail: Option<String>,
+    #[serde(default)]
+    pub users: u64,
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub gamerpower_url: Option<String>,
+    /// Name of the provider that listed the offer.
+    #[serde(default)]
+    pub provider: String,
+    /// Set while the provider keeps failing and the offer is carried over from an earlier fetch.
+    #[serde(default)]
+    pub stale_since: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    pub is_silenced: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum GiveawayType {
+    #[default]
+    Game,
+    Dlc,
+    Loot,
+    EarlyAccess,
+    Other,
+}
+
+impl GiveawayType {
+    pub const ALL: [GiveawayType; 5] = [
+        GiveawayType::Game,
+        GiveawayType::Dlc,
+        GiveawayType::Loot,
+        GiveawayType::EarlyAccess,
+        GiveawayType::Other,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GiveawayType::Game => "Game",
+            GiveawayType::Dlc => "DLC",
+            GiveawayType::Loot => "Loot",
+            GiveawayType::EarlyAccess => "Early Access",
+            GiveawayType::Other => "Other",
+        }
+    }
+}
+
+impl From<String> for GiveawayType {
+    fn from(value: String) -> Self {
+        match value.to_lowercase().as_str() {
+            "game" => GiveawayType::Game,
+            "dlc" => GiveawayType::Dlc,
+            "loot" => GiveawayType::Loot,
+            "early access" | "early_access" | "beta" => GiveawayType::EarlyAccess,
+            _ => GiveawayType::Other,
+        }
+    }
+}
+
+impl From<GiveawayType> for String {
+    fn from(value: GiveawayType) -> Self {
+        match value {
+            GiveawayType::Game => "game",
+            GiveawayType::Dlc => "dlc",
+            GiveawayType::Loot => "loot",
+            GiveawayType::EarlyAccess => "early_access",
+            GiveawayType::Other => "other",
+        }.to_string()
+    }
+}
+
+/// Reads a money value such as `"$19.99"`, gamerpower uses `"N/A"` when unknown.
+pub fn parse_worth(raw: &str) -> Option<f64> {
+    let amount: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+
+    amount.parse().ok()
+}
+
+/// Formats the time left until an offer ends, e.g. `"2d 4h"` or `"35m"`.
+/// Kept coarse on purpose, the tray is only rebuilt when this text changes.
+pub fn format_time_left(time_left: Duration) -> String {
+    let days = time_left.num_days();
+    let hours = time_left.num_hours() % 24;
+    let minutes = time_left.num_minutes();
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h", hours)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        "<1m".to_string()
+    }
+}
+
+/// Reads gamerpower's `"2025-05-01 23:59:00"` dates, which are given in UTC.
+pub fn parse_date(raw: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S") {
+        return Some(date.and_utc());
+    }
+
+    DateTime::parse_from_rfc3339(raw).ok().map(|date| date.with_timezone(&Utc))
+}
+
+/// Cuts `word` off the end of `text` when it stands on its own, ignoring case.
+fn strip_word_suffix<'a>(text: &'a str, word: &str) -> Option<&'a str> {
+    let start = text.len().checked_sub(word.len())?;
+    let rest = text.get(..start)?;
+
+    (text[start..].eq_ignore_ascii_case(word) && rest.ends_with(char::is_whitespace)).then(|| rest.trim_end())
+}
+
+fn deserialize_worth<'de, D>(deserializer: D) -> std::result::Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = Option::<String>::deserialize(deserializer)?;
+    Ok(raw.as_deref().and_then(parse_worth))
+}
+
+/// Writes the worth back the way gamerpower sends it, so cached offers read like fetched ones.
+fn serialize_worth<S>(worth: &Option<f64>, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    worth.map(|worth| worth.to_string()).serialize(serializer)
+}
+
+fn deserialize_date<'de, D>(deserializer: D) -> std::result::Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = Option::<String>::deserialize(deserializer)?;
+    Ok(raw.as_deref().and_then(parse_date))
+}
+
+fn deserialize_text<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = Option::<String>::deserialize(deserializer)?;
+    Ok(raw
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty() && !text.eq_ignore_ascii_case("N/A")))
+}
+
+impl Game {
+    /// Builds a stable numeric id for offers that do not come with one,
+    /// so they can be tracked the same way as gamerpower giveaways.
+    pub fn synthetic_id(provider: &str, key: &str) -> usize {
+        let digest = md5::compute(format!("{}:{}", provider, key));
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        u64::from_le_bytes(bytes) as usize
+    }
+
+    pub fn time_left(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.end_date.map(|end_date| end_date - now)
+    }
+
+    pub fn get_open_url(&self) -> &str {
+        if self.open_giveaway_url.is_empty() {
+            self.gamerpower_url.as_deref().unwrap_or_default()
+        } else {
+            &self.open_giveaway_url
+        }
+    }
+
+    pub fn get_image_url(&self) -> &str {
+        if self.image.is_empty() {
+            self.thumbnail.as_deref().unwrap_or_default()
+        } else {
+            &self.image
+        }
+    }
+
+    /// gamerpower names offers like "Hollow Knight (Epic Games) Giveaway" or "Hollow Knight Key Giveaway",
+    /// only the game's name is compared so they match the store's own listing.
+    fn dedup_key(&self) -> String {
+        let mut title = self.title.trim();
+
+        if let Some(rest) = strip_word_suffix(title, "giveaway") {
+            title = rest
+                .strip_suffix(')')
+                .and_then(|rest| rest.rsplit_once('('))
+                .map_or(rest, |(rest, _)| rest.trim_end());
+            title = strip_word_suffix(title, "key").unwrap_or(title);
+        }
+
+        let title: String = title
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect();
+
+        format!("{}:{}", self.platform.as_str(), title.to_lowercase())
+    }
 }
 
 #[derive(Debug, Clone)]
+pub struct ProviderError {
+    pub provider: &'static str,
+    pub error: FetchError,
+}
+
+#[derive(Clone)]
 pub struct Games {
+    providers: Arc<Vec<Box<dyn GiveawayProvider>>>,
     games: Arc<Mutex<Vec<Game>>>,
-    empty_reason: Arc<Mutex<Option<String>>>,
+    /// Offers as the providers listed them, a failing provider keeps its previous ones from here.
+    fetched_games: Arc<Mutex<Vec<Game>>>,
+    config: Config,
+    http: Arc<HttpCache>,
+    /// When the offers on display were fetched, they may come from the offer cache.
+    fetched_at: Arc<Mutex<Option<DateTime<Utc>>>>,
+    /// Set when the last refetch failed for every provider and older offers are still shown.
+    is_stale: Arc<Mutex<bool>>,
 }
 
 impl Games {
-    pub fn new() -> Self {
+    pub fn new(providers: Vec<Box<dyn GiveawayProvider>>, config: Config, http: Arc<HttpCache>) -> Self {
         Games {
+            providers: Arc::new(providers),
             games: Arc::new(Mutex::new(Vec::new())),
-            empty_reason: Arc::new(Mutex::new(None)),
+            fetched_games: Arc::new(Mutex::new(Vec::new())),
+            config,
+            http,
+            fetched_at: Arc::new(Mutex::new(None)),
+            is_stale: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    pub async fn get_stale_since(&self) -> Option<DateTime<Utc>> {
+        if *self.is_stale.lock().await {
+            *self.fetched_at.lock().await
+        } else {
+            None
         }
     }
 
-    pub async fn get_error_reason(&self) -> Option<String> {
-        let empty_reason = self.empty_reason.lock().await;
-        empty_reason.clone()
+    pub async fn get_error_reasons(&self) -> Vec<ProviderError> {
+        let mut reasons = Vec::new();
+
+        for provider in self.providers.iter() {
+            if let ProviderHealth::Failing { error, .. } = provider.health().await {
+                reasons.push(ProviderError {
+                    provider: provider.name(),
+                    error,
+                });
+            }
+        }
+
+        reasons
     }
 
-    async fn set_error_reason(&self, reason: String) {
-        let mut empty_reason = self.empty_reason.lock().await;
-        *empty_reason = Some(reason);
+    /// Returns `false` when the same offers with the same end dates are listed as before and nothing failed
+    /// before or now, so there is nothing new to notify about or show. Unchanged responses don't mean
+    /// unchanged offers, an upcoming one may have started since.
+    pub async fn refetch(&mut self) -> Result<bool> {
+        let had_errors = !self.get_error_reasons().await.is_empty();
+        let previous_offers = self.get_offer_keys().await;
+        let previous_games = self.fetched_games.lock().await.clone();
+        let previous_fetched_at = *self.fetched_at.lock().await;
+        let mut fetched_games: Vec<Game> = Vec::new();
+        let mut has_succeeded = false;
+        let mut has_failed = false;
+
+        self.http.begin_refresh().await;
+
+        for provider in self.providers.iter() {
+            match provider.fetch().await {
+                Ok(games) => {
+                    has_succeeded = true;
+                    fetched_games.extend(games.into_iter().map(|game| Game {
+                        provider: provider.name().to_string(),
+                        stale_since: None,
+                        ..game
+                    }));
+                }
+                Err(e) => {
+                    tracing::error!("Provider {} failed: {}", provider.name(), e.detail());
+                    has_failed = true;
+
+                    // One provider being down shouldn't take its offers off the list
+                    fetched_games.extend(previous_games.iter()
+                        .filter(|game| game.provider == provider.name())
+                        .map(|game| Game {
+                            stale_since: game.stale_since.or(previous_fetched_at),
+                            ..game.clone()
+                        }));
+                }
+            }
+        }
+
+        // A failing provider may not have gotten to all of its URLs, their entries are still good
+        self.http.finish_refresh(!has_failed).await;
+
+        if let Err(e) = self.http.save().await {
+            tracing::error!("Failed to save HTTP cache: {:#}", e);
+        }
+
+        // Nothing came through, most likely offline, keep showing what we already have
+        if !has_succeeded && !self.providers.is_empty() {
+            *self.is_stale.lock().await = self.fetched_at.lock().await.is_some();
+            return Ok(true);
+        }
+
+        let fetched_at = Utc::now();
+        let cache = OfferCache {
+            fetched_at,
+            games: fetched_games,
+        };
+
+        if let Err(e) = cache.save() {
+            tracing::error!("Failed to save offer cache: {:#}", e);
+        }
+
+        *self.fetched_at.lock().await = Some(fetched_at);
+        *self.is_stale.lock().await = false;
+        self.set_games(cache.games.clone()).await;
+        *self.fetched_games.lock().await = cache.games;
+
+        let has_errors = !self.get_error_reasons().await.is_empty();
+
+        Ok(self.get_offer_keys().await != previous_offers || had_errors || has_errors)
     }
 
-    async fn clear_error_reason(&self) {
-        let mut empty_reason = self.empty_reason.lock().await;
-        *empty_reason = None;
+    async fn get_offer_keys(&self) -> HashSet<(usize, Option<DateTime<Utc>>)> {
+        self.get_all().await
+            .iter()
+            .map(|game| (game.id, game.end_date))
+            .collect()
     }
 
-    async fn consume_response(&self, response: reqwest::Response) -> Result<()> {
-        self.clear_error_reason().await;
+    /// Shows the cached offers until the first refetch replaces them.
+    async fn restore(&self, cache: OfferCache) {
+        *self.fetched_at.lock().await = Some(cache.fetched_at);
+        self.set_games(cache.games.clone()).await;
+        *self.fetched_games.lock().await = cache.games;
+    }
 
-        let raw_games: Vec<Game> = response.json().await.context("Failed to parse JSON from API")?;
+    /// Filters and orders offers straight from the providers, the settings may have changed since they were cached.
+    async fn set_games(&self, games: Vec<Game>) {
+        let mut seen = HashSet::new();
+        let platforms = self.config.get_platforms().await;
+        let giveaway_types = self.config.get_giveaway_types().await;
+        let rules = RuleSet::new(self.config.get_rules().await);
 
-        let filtered_games: Vec<Game> = raw_games
+        // The same offer is often listed by more than one provider
+        let mut games: Vec<Game> = games
             .into_iter()
-            .filter(|g| g.game_type.eq_ignore_ascii_case("game"))
-            .filter(|g| g.status.eq_ignore_ascii_case("active"))
-            .filter_map(|mut g| {
-                let p = g.platforms.to_lowercase();
-                g.platform = if p.contains("steam") {
-                    "steam".into()
-                } else if p.contains("epic") {
-                    "epic".into()
-                } else if p.contains("gog") {
-                    "gog".into()
-                } else {
-                    return None;
-                };
-                Some(g)
+            .filter(|g| platforms.is_enabled(g.platform))
+            .filter(|g| giveaway_types.is_enabled(g.game_type))
+            .filter(|g| seen.insert(g.dedup_key()))
+            .filter_map(|mut g| match rules.evaluate(&g) {
+                Verdict::Show => Some(g),
+                Verdict::Silence => {
+                    g.is_silenced = true;
+                    Some(g)
+                }
+                Verdict::Hide => None,
             })
             .collect();
 
-        let mut data = self.games.lock().await;
-        *data = filtered_games;
-
-        Ok(())
-    }
-
-    pub async fn refetch(&mut self) -> Result<()> {
-        let url = "https://www.gamerpower.com/api/giveaways?platform=pc";
-        match reqwest::get(url).await {
-            Ok(response) => {
-                if !response.status().is_success() {
-                    match response.status() {
-                        StatusCode::NOT_FOUND => {
-                            self.set_error_reason("No active giveaways".to_string()).await;
-                        }
-                        StatusCode::INTERNAL_SERVER_ERROR => {
-                            self.set_error_reason("Server error".to_string()).await;
-                        }
-                        _ => {
-                            self.set_error_reason("Unknown error".to_string()).await;
-                        }
-                    }
-                    return Ok(());
-                }
+        // Offers ending soonest go first, open-ended ones follow from the newest
+        games.sort_by(|a, b| match (a.end_date, b.end_date) {
+            (Some(a_end), Some(b_end)) => a_end.cmp(&b_end),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => b.published_date.cmp(&a.published_date),
+        });
 
-                self.consume_response(response).await.context("Failed to consume response")
-            }
-            Err(e) => {
-                let reason = if e.is_connect() {
-                    "Network unavailable".to_string()
-                } else if e.is_timeout() {
-                    "Request timed out".to_string()
-                } else {
-                    "Unknown network error".to_string()
-                };
-
-                self.set_error_reason(reason).await;
-                return Ok(());
-            }
-        }
+        *self.games.lock().await = games;
     }
 
     pub async fn get_all(&self) -> Vec<Game> {
         let data = self.games.lock().await;
-        data.clone()
+        let now = Utc::now();
+
+        // Expired offers stay around until the next refetch, but are no use to anyone
+        data.iter()
+            .filter(|g| g.end_date.is_none_or(|end_date| end_date > now))
+            .cloned()
+            .collect()
     }
 
-    pub async fn fetch() -> Result<Self> {
-        let mut games = Games::new();
+    pub async fn fetch(config: &Config) -> Result<Self> {
+        let http = Arc::new(HttpCache::new());
+        http.load_or_init().await.context("Failed to load HTTP cache")?;
+
+        let providers = enabled_providers(&config.get_providers().await, &http);
+        let mut games = Games::new(providers, config.clone(), http);
+
+        if let Some(cache) = OfferCache::load() {
+            games.restore(cache).await;
+        }
+
         games.refetch().await.context("Failed to fetch games")?;
         Ok(games)
     }
-}
\ No newline at end of file
+
+    /// The offers saved by the last fetch, for reading while another instance owns the state files.
+    pub async fn from_cache(config: &Config) -> Self {
+        let games = Games::new(Vec::new(), config.clone(), Arc::new(HttpCache::new()));
+
+        if let Some(cache) = OfferCache::load() {
+            games.restore(cache).await;
+        }
+
+        games
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::providers::{EpicProvider, GamerpowerProvider};
+
+    /// Lists whatever the test put in, the way a provider answering from its HTTP cache would.
+    struct ListedOffers {
+        games: Arc<std::sync::Mutex<Vec<Game>>>,
+    }
+
+    #[async_trait]
+    impl GiveawayProvider for ListedOffers {
+        fn name(&self) -> &'static str {
+            "listed"
+        }
+
+        async fn fetch(&self) -> std::result::Result<Vec<Game>, FetchError> {
+            Ok(self.games.lock().unwrap().clone())
+        }
+
+        async fn health(&self) -> ProviderHealth {
+            ProviderHealth::Healthy
+        }
+    }
+
+    fn gamerpower_game() -> Game {
+        serde_json::from_value(json!({
+            "id": 3101,
+            "title": "Hollow Lantern (Epic Games) Giveaway",
+            "worth": "$19.99",
+            "thumbnail": "  ",
+            "image": "https://cdn.example/gp/3101.jpg",
+            "description": "  Grab it while it lasts.\n",
+            "instructions": "N/A",
+            "open_giveaway_url": "https://www.gamerpower.com/open/3101",
+            "published_date": "2025-10-02 15:00:00",
+            "type": "Early Access",
+            "platforms": "PC, Epic Games Store",
+            "end_date": "2025-10-09 15:00:00",
+            "status": "Active",
+        })).unwrap()
+    }
+
+    #[test]
+    fn parses_worth() {
+        assert_eq!(parse_worth("$19.99"), Some(19.99));
+        assert_eq!(parse_worth("€5"), Some(5.0));
+        assert_eq!(parse_worth("N/A"), None);
+        assert_eq!(parse_worth(""), None);
+    }
+
+    #[test]
+    fn parses_dates() {
+        let expected = "2025-05-01T23:59:00Z".parse().ok();
+
+        assert_eq!(parse_date("2025-05-01 23:59:00"), expected);
+        assert_eq!(parse_date("2025-05-01T23:59:00Z"), expected);
+        assert_eq!(parse_date("2025-05-02T01:59:00+02:00"), expected);
+        assert_eq!(parse_date("N/A"), None);
+    }
+
+    #[test]
+    fn reads_gamerpower_fields() {
+        let game = gamerpower_game();
+
+        assert_eq!(game.worth, Some(19.99));
+        assert_eq!(game.end_date, "2025-10-09T15:00:00Z".parse().ok());
+        assert_eq!(game.published_date, "2025-10-02T15:00:00Z".parse().ok());
+        assert_eq!(game.game_type, GiveawayType::EarlyAccess);
+        assert_eq!(game.description.as_deref(), Some("Grab it while it lasts."));
+        assert_eq!(game.instructions, None);
+        assert_eq!(game.thumbnail, None);
+        assert_eq!(game.users, 0);
+    }
+
+    #[test]
+    fn round_trips_through_the_offer_cache() {
+        let cache = OfferCache {
+            fetched_at: "2025-10-03T08:00:00Z".parse().unwrap(),
+            games: vec![gamerpower_game()],
+        };
+
+        let json = serde_json::to_string(&cache).unwrap();
+        let restored: OfferCache = serde_json::from_str(&json).unwrap();
+        let (game, restored_game) = (&cache.games[0], &restored.games[0]);
+
+        assert_eq!(restored.fetched_at, cache.fetched_at);
+        assert_eq!(restored_game.worth, game.worth);
+        assert_eq!(restored_game.end_date, game.end_date);
+        assert_eq!(restored_game.published_date, game.published_date);
+        assert_eq!(restored_game.game_type, game.game_type);
+        assert_eq!(restored_game.description, game.description);
+    }
+
+    fn titled(title: &str, platform: Platform) -> Game {
+        Game {
+            title: title.to_string(),
+            platform,
+            ..Game::default()
+        }
+    }
+
+    #[test]
+    fn compares_gamerpower_titles_by_the_game_name() {
+        let store = titled("Hollow Lantern", Platform::Epic).dedup_key();
+
+        assert_eq!(titled("Hollow Lantern (Epic Games) Giveaway", Platform::Epic).dedup_key(), store);
+        assert_eq!(titled("Hollow Lantern Key Giveaway", Platform::Epic).dedup_key(), store);
+        assert_eq!(titled("hollow lantern giveaway", Platform::Epic).dedup_key(), store);
+
+        assert_ne!(titled("Hollow Lantern (Steam) Giveaway", Platform::Steam).dedup_key(), store);
+        assert_ne!(titled("Monkey Giveaway", Platform::Epic).dedup_key(), titled("Mon", Platform::Epic).dedup_key());
+        assert_ne!(titled("Hollow Lantern: Giveaway Edition", Platform::Epic).dedup_key(), store);
+    }
+
+    #[tokio::test]
+    async fn keeps_one_entry_for_offers_listed_by_the_store_and_gamerpower() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/freeGamesPromotions"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../tests/fixtures/epic_promotions.json")))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/giveaways"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../tests/fixtures/gamerpower_giveaways.json")))
+            .mount(&server)
+            .await;
+
+        let http = Arc::new(HttpCache::new());
+        let providers: Vec<Box<dyn GiveawayProvider>> = vec![
+            Box::new(EpicProvider::with_base_url(&server.uri(), http.clone())),
+            Box::new(GamerpowerProvider::with_base_url(&server.uri(), http.clone())),
+        ];
+
+        let mut offers = Vec::new();
+
+        for provider in &providers {
+            offers.extend(provider.fetch().await.unwrap());
+        }
+
+        let games = Games::new(providers, Config::new(), http);
+        games.set_games(offers).await;
+
+        let lanterns: Vec<usize> = games.get_all().await
+            .iter()
+            .filter(|g| g.title.starts_with("Hollow Lantern"))
+            .map(|g| g.id)
+            .collect();
+
+        // The store's own entry wins
+        assert_eq!(lanterns, [Game::synthetic_id("epic", "a1b2c3")]);
+    }
+
+    #[tokio::test]
+    async fn reports_changes_by_the_listed_offers() {
+        let listed = Arc::new(std::sync::Mutex::new(vec![Game { id: 1, ..titled("Hollow Lantern", Platform::Epic) }]));
+        let provider = ListedOffers { games: Arc::clone(&listed) };
+        let mut games = Games::new(vec![Box::new(provider)], Config::new(), Arc::new(HttpCache::new()));
+
+        assert!(games.refetch().await.unwrap());
+        assert!(!games.refetch().await.unwrap());
+
+        // An upcoming offer went live, the response itself may well be the same
+        listed.lock().unwrap().push(Game { id: 2, ..titled("Cinder Rally", Platform::Epic) });
+        assert!(games.refetch().await.unwrap());
+
+        listed.lock().unwrap()[0].end_date = Some(Utc::now() + Duration::days(3));
+        assert!(games.refetch().await.unwrap());
+        assert!(!games.refetch().await.unwrap());
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+use tokio::{
+    sync::mpsc,
+    time::{interval, sleep_until, Duration, Instant, MissedTickBehavior},
+};
+
+use crate::{
+    claims::Claims,
+    config::Config,
+    games::Games,
+    notifications::Notifications,
+    schedule::RefreshSchedule,
+    tray::Message,
+};
+
+const REMINDERS_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Fetch-and-notify loop without any GUI, runs until SIGTERM, Ctrl+C or a `quit` command.
+pub async fn run(
+    mut games: Games,
+    claims: Claims,
+    mut config: Config,
+    notifications: Notifications,
+    mut schedule: RefreshSchedule,
+    mut control: mpsc::UnboundedReceiver<Message>,
+) -> Result<()> {
+    tracing::info!("FreeTrayGames is running headless");
+
+    let mut reminders = interval(REMINDERS_INTERVAL);
+    reminders.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    // Offers were just fetched on startup, the first tick fires right away
+    reminders.tick().await;
+
+    let mut next_refresh = Instant::now() + schedule.next_delay(&games).await;
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = sleep_until(next_refresh) => {
+                if games.refetch().await? {
+                    notifications.push_all_new_games().await?;
+                }
+
+                next_refresh = Instant::now() + schedule.next_delay(&games).await;
+            }
+            _ = reminders.tick() => {
+                notifications.push_due_reminders().await?;
+            }
+            Some(message) = control.recv() => match message {
+                Message::Quit => break,
+                Message::Refresh => {
+                    if games.refetch().await? {
+                        notifications.push_all_new_games().await?;
+                    }
+
+                    next_refresh = Instant::now() + schedule.next_delay(&games).await;
+                }
+                Message::ToggleNotifications => {
+                    config.toggle_notifications().await;
+                    if let Err(e) = config.save().await {
+                        tracing::warn!("Notifications toggled for this session only: {:#}", e);
+                    }
+                    notifications.push_all_new_games().await?;
+                }
+                Message::MarkClaimed(id) => {
+                    let games = games.get_all().await;
+
+                    if let Some(game) = games.iter().find(|g| g.id == id) {
+                        claims.mark_claimed(game).await;
+                        claims.save().await?;
+                    }
+                }
+                _ => {}
+            },
+            result = &mut shutdown => {
+                result?;
+                break;
+            }
+        }
+    }
+
+    tracing::info!("Shutting down");
+    notifications.save().await.context("Failed to save notification state")?;
+
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate()).context("Failed to listen for SIGTERM")?;
+
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result.context("Failed to listen for SIGINT")?,
+        _ = terminate.recv() => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() -> Result<()> {
+    tokio::signal::ctrl_c().await.context("Failed to listen for Ctrl+C")
+}
@@ -0,0 +1,386 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    mem,
+    path::PathBuf,
+    sync::OnceLock,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{
+    header::{CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    Certificate, Client, NoProxy, Proxy, Response, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::{config::NetworkConfig, providers::FetchError};
+
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Builds the shared client from the `[network]` settings, must run before the first request.
+pub fn init(config: &NetworkConfig) -> Result<()> {
+    let mut builder = Client::builder()
+        .user_agent(config.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT))
+        .connect_timeout(std::time::Duration::from_secs(config.connect_timeout_secs));
+
+    if config.timeout_secs > 0 {
+        builder = builder.timeout(std::time::Duration::from_secs(config.timeout_secs));
+    }
+
+    if let Some(proxy_url) = &config.proxy {
+        let proxy = Proxy::all(proxy_url)
+            .context("Invalid proxy URL")?
+            .no_proxy(NoProxy::from_string(&config.no_proxy.join(",")));
+
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(ca_bundle) = &config.ca_bundle {
+        let pem = fs::read(ca_bundle).with_context(|| format!("Failed to read CA bundle {}", ca_bundle))?;
+        let certificates = Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid CA bundle {}", ca_bundle))?;
+
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    let client = builder.build().context("Failed to build HTTP client")?;
+
+    if CLIENT.set(client).is_err() {
+        bail!("HTTP client is already initialized");
+    }
+
+    Ok(())
+}
+
+/// One client for every outbound request, so connections are pooled and settings apply everywhere.
+pub fn client() -> &'static Client {
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()
+            .expect("Failed to build HTTP client")
+    })
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct CacheEntry {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Until when the body can be reused without asking, from `Cache-Control: max-age`.
+    fresh_until: Option<DateTime<Utc>>,
+    body: String,
+}
+
+/// What the refresh in progress asked for.
+#[derive(Default)]
+struct RefreshState {
+    requested: HashSet<String>,
+}
+
+/// Provider responses together with their validators, so refreshes only download what changed.
+pub struct HttpCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    refresh: Mutex<RefreshState>,
+}
+
+impl HttpCache {
+    pub fn new() -> Self {
+        HttpCache {
+            entries: Mutex::new(HashMap::new()),
+            refresh: Mutex::new(RefreshState::default()),
+        }
+    }
+
+    pub async fn load_or_init(&self) -> Result<()> {
+        let path = Self::get_cache_path();
+
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let file = File::open(&path).context("Failed to open HTTP cache")?;
+        let reader = BufReader::new(file);
+        let entries: HashMap<String, CacheEntry> = serde_json::from_reader(reader).unwrap_or_default();
+
+        *self.entries.lock().await = entries;
+
+        Ok(())
+    }
+
+    pub fn get_cache_path() -> PathBuf {
+        let mut exe_path = env::current_exe().unwrap();
+        exe_path.set_file_name("http_cache");
+        exe_path.set_extension("json");
+        exe_path
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        let path = Self::get_cache_path();
+        let file = File::create(&path).context("Failed to create HTTP cache")?;
+        let mut writer = BufWriter::new(file);
+
+        let entries = self.entries.lock().await.clone();
+        let json = serde_json::to_string(&entries).context("Failed to serialize HTTP cache")?;
+        writer.write_all(json.as_bytes()).context("Failed to write HTTP cache")?;
+
+        Ok(())
+    }
+
+    pub async fn begin_refresh(&self) {
+        *self.refresh.lock().await = RefreshState::default();
+    }
+
+    /// With `evict` the entries the refresh didn't ask for are dropped, like pages of offers that are long gone.
+    pub async fn finish_refresh(&self, evict: bool) {
+        let refresh = mem::take(&mut *self.refresh.lock().await);
+
+        if evict {
+            self.entries.lock().await.retain(|url, _| refresh.requested.contains(url));
+        }
+    }
+
+    /// GETs the body of the URL, reusing the cached one while it's fresh or the server says it's unchanged.
+    pub async fn get(&self, url: &str) -> Result<String, FetchError> {
+        let now = Utc::now();
+        let cached = self.entries.lock().await.get(url).cloned();
+
+        self.refresh.lock().await.requested.insert(url.to_string());
+
+        if let Some(entry) = &cached {
+            if entry.fresh_until.is_some_and(|fresh_until| fresh_until > now) {
+                return Ok(entry.body.clone());
+            }
+        }
+
+        let mut request = client().get(url);
+
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send().await.map_err(|e| FetchError::from_reqwest(url, e))?;
+
+        if let (StatusCode::NOT_MODIFIED, Some(mut entry)) = (response.status(), cached) {
+            entry.fresh_until = get_fresh_until(&response, now);
+            let body = entry.body.clone();
+            self.entries.lock().await.insert(url.to_string(), entry);
+
+            return Ok(body);
+        }
+
+        if !response.status().is_success() {
+            return Err(FetchError::from_status(url, &response));
+        }
+
+        let etag = get_header(&response, ETAG);
+        let last_modified = get_header(&response, LAST_MODIFIED);
+        let fresh_until = get_fresh_until(&response, now);
+        let is_cacheable = !has_directive(&response, "no-store")
+            && (etag.is_some() || last_modified.is_some() || fresh_until.is_some());
+
+        let body = response.text().await.map_err(|e| FetchError::from_reqwest(url, e))?;
+
+        let mut entries = self.entries.lock().await;
+
+        if is_cacheable {
+            entries.insert(url.to_string(), CacheEntry {
+                etag,
+                last_modified,
+                fresh_until,
+                body: body.clone(),
+            });
+        } else {
+            entries.remove(url);
+        }
+
+        Ok(body)
+    }
+}
+
+fn get_header(response: &Response, name: reqwest::header::HeaderName) -> Option<String> {
+    response.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+fn has_directive(response: &Response, directive: &str) -> bool {
+    get_header(response, CACHE_CONTROL).is_some_and(|cache_control| {
+        cache_control.split(',').any(|d| d.trim().eq_ignore_ascii_case(directive))
+    })
+}
+
+/// `no-cache` means the body may be kept, but has to be revalidated every time.
+fn get_fresh_until(response: &Response, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if has_directive(response, "no-cache") {
+        return None;
+    }
+
+    let cache_control = get_header(response, CACHE_CONTROL)?;
+    let max_age: i64 = cache_control.split(',')
+        .find_map(|d| d.trim().strip_prefix("max-age="))?
+        .trim_matches('"')
+        .parse()
+        .ok()?;
+
+    Some(now + Duration::seconds(max_age))
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{header, header_exists, method, path},
+        Mock, MockServer, Request, ResponseTemplate,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn revalidates_with_validators_and_reuses_body_on_304() {
+        let server = MockServer::start().await;
+        let url = format!("{}/offers", server.uri());
+
+        Mock::given(method("GET"))
+            .and(path("/offers"))
+            .and(header("If-None-Match", "\"v1\""))
+            // `header` would split the date on its comma
+            .and(|request: &Request| {
+                request.headers.get(IF_MODIFIED_SINCE).is_some_and(|value| value == "Wed, 01 Oct 2025 10:00:00 GMT")
+            })
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/offers"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .insert_header("Last-Modified", "Wed, 01 Oct 2025 10:00:00 GMT")
+                .set_body_string("[1, 2]"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let cache = HttpCache::new();
+
+        cache.begin_refresh().await;
+        assert_eq!(cache.get(&url).await.unwrap(), "[1, 2]");
+        cache.finish_refresh(true).await;
+
+        cache.begin_refresh().await;
+        assert_eq!(cache.get(&url).await.unwrap(), "[1, 2]");
+        cache.finish_refresh(true).await;
+    }
+
+    #[tokio::test]
+    async fn serves_fresh_entries_without_asking() {
+        let server = MockServer::start().await;
+        let url = format!("{}/offers", server.uri());
+
+        Mock::given(method("GET"))
+            .and(path("/offers"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("Cache-Control", "max-age=600")
+                .set_body_string("fresh"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let cache = HttpCache::new();
+
+        assert_eq!(cache.get(&url).await.unwrap(), "fresh");
+        assert_eq!(cache.get(&url).await.unwrap(), "fresh");
+    }
+
+    #[tokio::test]
+    async fn skips_no_store_responses() {
+        let server = MockServer::start().await;
+        let url = format!("{}/offers", server.uri());
+
+        Mock::given(method("GET"))
+            .and(path("/offers"))
+            .and(header_exists("If-None-Match"))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/offers"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .insert_header("Cache-Control", "no-store")
+                .set_body_string("private"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let cache = HttpCache::new();
+
+        cache.get(&url).await.unwrap();
+        cache.get(&url).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn evicts_entries_the_refresh_did_not_request() {
+        let server = MockServer::start().await;
+        let kept = format!("{}/kept", server.uri());
+        let gone = format!("{}/gone", server.uri());
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_string("body"))
+            .mount(&server)
+            .await;
+
+        let cache = HttpCache::new();
+
+        cache.begin_refresh().await;
+        cache.get(&kept).await.unwrap();
+        cache.get(&gone).await.unwrap();
+        cache.finish_refresh(true).await;
+
+        // A failed refresh keeps everything, it may not have gotten to every URL
+        cache.begin_refresh().await;
+        cache.get(&kept).await.unwrap();
+        cache.finish_refresh(false).await;
+        assert!(cache.entries.lock().await.contains_key(&gone));
+
+        cache.begin_refresh().await;
+        cache.get(&kept).await.unwrap();
+        cache.finish_refresh(true).await;
+
+        let entries = cache.entries.lock().await;
+        assert!(entries.contains_key(&kept));
+        assert!(!entries.contains_key(&gone));
+    }
+
+    #[tokio::test]
+    async fn reports_error_statuses() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let cache = HttpCache::new();
+
+        assert!(cache.get(&format!("{}/offers", server.uri())).await.is_err());
+    }
+}
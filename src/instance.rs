@@ -0,0 +1,308 @@
+use std::{env, fs::{File, TryLockError}, io::ErrorKind, path::PathBuf};
+use anyhow::{bail, Context, Result};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    sync::mpsc,
+};
+
+use crate::tray::Message;
+
+/// Held for the whole life of the tray or headless instance, released by the OS when the process dies.
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Returns `None` when another instance already holds the lock.
+    pub fn acquire() -> Result<Option<Self>> {
+        let file = File::create(Self::get_lock_path()).context("Failed to create instance lock")?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Some(InstanceLock { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e).context("Failed to lock instance"),
+        }
+    }
+
+    pub fn get_lock_path() -> PathBuf {
+        let mut exe_path = env::current_exe().unwrap();
+        exe_path.set_file_name("instance");
+        exe_path.set_extension("lock");
+        exe_path
+    }
+}
+
+/// Control commands travel as single lines, only the ones that make sense from outside are accepted.
+fn encode(message: &Message) -> Option<String> {
+    match message {
+        Message::Quit => Some("quit".to_string()),
+        Message::Refresh => Some("refresh".to_string()),
+        Message::ToggleNotifications => Some("toggle-notifications".to_string()),
+        Message::MarkClaimed(id) => Some(format!("mark-claimed {}", id)),
+        _ => None,
+    }
+}
+
+fn decode(line: &str) -> Option<Message> {
+    match line.trim().split_once(' ') {
+        Some(("mark-claimed", id)) => id.parse().ok().map(Message::MarkClaimed),
+        Some(_) => None,
+        None => match line.trim() {
+            "quit" => Some(Message::Quit),
+            "refresh" => Some(Message::Refresh),
+            "toggle-notifications" => Some(Message::ToggleNotifications),
+            _ => None,
+        },
+    }
+}
+
+/// Every install gets its own channel, just like it gets its own config and state files.
+fn get_channel_name() -> String {
+    let exe_path = env::current_exe().unwrap();
+    let hash = format!("{:x}", md5::compute(exe_path.to_string_lossy().as_bytes()));
+
+    format!("free-tray-games-{}", &hash[..12])
+}
+
+/// `XDG_RUNTIME_DIR` is private to the user already, the shared temp dir gets a private subdirectory.
+#[cfg(unix)]
+fn get_socket_dir() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => env::temp_dir().join(format!("free-tray-games-{}", unsafe { libc::getuid() })),
+    }
+}
+
+#[cfg(unix)]
+fn get_socket_path() -> PathBuf {
+    // Socket paths are limited to around 100 bytes, so they can't live next to the exe
+    get_socket_dir().join(format!("{}.sock", get_channel_name()))
+}
+
+/// Creates the socket directory readable by nobody else, or makes sure an existing one still is.
+#[cfg(unix)]
+fn prepare_socket_dir() -> Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    let dir = get_socket_dir();
+
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e).context("Failed to create control socket directory"),
+    }
+
+    let metadata = std::fs::symlink_metadata(&dir).context("Failed to inspect control socket directory")?;
+
+    if !metadata.is_dir() || metadata.uid() != unsafe { libc::getuid() } || metadata.mode() & 0o077 != 0 {
+        bail!("{} is not a private directory of the current user", dir.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn get_pipe_name() -> String {
+    format!(r"\\.\pipe\{}", get_channel_name())
+}
+
+/// Starts accepting control commands, the lock proves no other instance is listening.
+#[cfg(unix)]
+pub fn serve(_lock: &InstanceLock) -> Result<mpsc::UnboundedReceiver<Message>> {
+    use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+    use tokio::net::UnixListener;
+
+    prepare_socket_dir()?;
+
+    let path = get_socket_path();
+
+    // Leftover from an instance that didn't shut down cleanly, nobody else can be listening
+    if path.exists() {
+        std::fs::remove_file(&path).context("Failed to remove stale control socket")?;
+    }
+
+    let listener = UnixListener::bind(&path).context("Failed to create control socket")?;
+    std::fs::set_permissions(&path, Permissions::from_mode(0o600))
+        .context("Failed to restrict control socket")?;
+
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                // Other users may still reach the socket through a shared `XDG_RUNTIME_DIR` or a lax umask
+                Ok((stream, _)) => match stream.peer_cred() {
+                    Ok(cred) if cred.uid() == unsafe { libc::getuid() } => {
+                        tokio::spawn(handle_connection(stream, tx.clone()));
+                    }
+                    Ok(cred) => tracing::warn!("Refused control connection from uid {}", cred.uid()),
+                    Err(e) => tracing::warn!("Refused control connection of unknown origin: {}", e),
+                },
+                Err(e) => {
+                    tracing::error!("Control socket failed: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(rx)
+}
+
+/// Starts accepting control commands, the lock proves no other instance is listening.
+#[cfg(windows)]
+pub fn serve(_lock: &InstanceLock) -> Result<mpsc::UnboundedReceiver<Message>> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = get_pipe_name();
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&name)
+        .context("Failed to create control pipe")?;
+
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = server.connect().await {
+                tracing::error!("Control pipe failed: {}", e);
+                break;
+            }
+
+            let connected = server;
+
+            server = match ServerOptions::new().create(&name) {
+                Ok(server) => server,
+                Err(e) => {
+                    tracing::error!("Failed to create control pipe: {}", e);
+                    break;
+                }
+            };
+
+            tokio::spawn(handle_connection(connected, tx.clone()));
+        }
+    });
+
+    Ok(rx)
+}
+
+async fn handle_connection<S>(stream: S, tx: mpsc::UnboundedSender<Message>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    if reader.read_line(&mut line).await.is_err() {
+        return;
+    }
+
+    let reply = match decode(&line).map(|message| tx.send(message)) {
+        Some(Ok(())) => "ok\n",
+        Some(Err(_)) => "error: instance is shutting down\n",
+        None => "error: unknown command\n",
+    };
+
+    let _ = reader.get_mut().write_all(reply.as_bytes()).await;
+}
+
+/// Forwards the message to the running instance. Returns `false` when there is none.
+pub async fn send(message: &Message) -> Result<bool> {
+    let Some(line) = encode(message) else {
+        bail!("{:?} can't be sent to the running instance", message);
+    };
+
+    let Some(stream) = connect().await? else {
+        return Ok(false);
+    };
+
+    let mut reader = BufReader::new(stream);
+    reader.get_mut().write_all(format!("{}\n", line).as_bytes()).await
+        .context("Failed to send command")?;
+
+    let mut reply = String::new();
+    reader.read_line(&mut reply).await.context("Failed to read reply")?;
+
+    match reply.trim() {
+        "ok" => Ok(true),
+        reply => bail!("Running instance refused the command: {}", reply),
+    }
+}
+
+#[cfg(unix)]
+async fn connect() -> Result<Option<tokio::net::UnixStream>> {
+    match tokio::net::UnixStream::connect(get_socket_path()).await {
+        Ok(stream) => Ok(Some(stream)),
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => Ok(None),
+        Err(e) => Err(e).context("Failed to connect to the running instance"),
+    }
+}
+
+#[cfg(windows)]
+async fn connect() -> Result<Option<tokio::net::windows::named_pipe::NamedPipeClient>> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+
+    match ClientOptions::new().open(get_pipe_name()) {
+        Ok(client) => Ok(Some(client)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).context("Failed to connect to the running instance"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    #[test]
+    fn round_trips_forwardable_messages() {
+        let messages = [
+            Message::Quit,
+            Message::Refresh,
+            Message::ToggleNotifications,
+            Message::MarkClaimed(3101),
+        ];
+
+        for message in messages {
+            let line = encode(&message).unwrap();
+            assert_eq!(decode(&format!("{}\n", line)), Some(message));
+        }
+    }
+
+    #[test]
+    fn keeps_tray_only_messages_to_itself() {
+        assert_eq!(encode(&Message::OpenGame(1)), None);
+        assert_eq!(encode(&Message::UnmarkClaimed(1)), None);
+        assert_eq!(encode(&Message::CheckReminders), None);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let lines = ["", "\n", "QUIT", "quit now", "mark-claimed", "mark-claimed abc", "mark-claimed -1", "open-game 1"];
+
+        for line in lines {
+            assert_eq!(decode(line), None, "{:?}", line);
+        }
+    }
+
+    #[tokio::test]
+    async fn replies_to_every_command() {
+        for (line, reply, expected) in [
+            ("mark-claimed 7\n", "ok\n", Some(Message::MarkClaimed(7))),
+            ("bogus\n", "error: unknown command\n", None),
+        ] {
+            let (mut client, server) = duplex(64);
+            let (tx, mut rx) = mpsc::unbounded_channel();
+
+            client.write_all(line.as_bytes()).await.unwrap();
+            handle_connection(server, tx).await;
+
+            let mut received = String::new();
+            BufReader::new(client).read_line(&mut received).await.unwrap();
+
+            assert_eq!(received, reply);
+            assert_eq!(rx.recv().await, expected);
+        }
+    }
+}
@@ -1,8 +1,12 @@
 use anyhow::Result;
 use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
-pub fn init_logger() -> Result<()> {
-    let log_writer = {
+/// The tray only keeps errors in error.log next to the executable, headless mode logs
+/// everything down to info to stderr, where service managers and containers pick it up.
+pub fn init_logger(is_headless: bool) -> Result<()> {
+    let log_writer = if is_headless {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
         let exe_dir = std::env::current_exe()?.parent().unwrap().to_path_buf();
         let log_path = exe_dir.join("error.log");
 
@@ -15,10 +19,16 @@ pub fn init_logger() -> Result<()> {
         })
     };
 
+    let max_level = if is_headless {
+        tracing::Level::INFO
+    } else {
+        tracing::Level::ERROR
+    };
+
     tracing_subscriber::fmt()
         .with_writer(log_writer)
         .with_ansi(false)
-        .with_max_level(tracing::Level::ERROR)
+        .with_max_level(max_level)
         .with_target(false)
         .with_line_number(true)
         .with_file(true)
@@ -29,4 +39,4 @@ pub fn init_logger() -> Result<()> {
     }));
 
     Ok(())
-}
\ No newline at end of file
+}
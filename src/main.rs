@@ -1,5 +1,7 @@
 #![windows_subsystem = "windows"]
+use std::{process::ExitCode, sync::Arc};
 use anyhow::{Context, Result};
+use tokio::sync::Notify;
 
 mod config;
 use config::Config;
@@ -7,6 +9,10 @@ use config::Config;
 mod games;
 use games::Games;
 
+mod cache;
+
+mod http;
+
 mod notifications;
 use notifications::Notifications;
 
@@ -16,48 +22,139 @@ mod resource;
 mod tray;
 use tray::{Message, Tray};
 
+mod menu;
+
 mod logger;
 use logger::init_logger;
 
+#[cfg(windows)]
 mod notify_body;
 
+mod providers;
+
+mod platform;
+
+mod rules;
+
+mod claims;
+use claims::Claims;
+
+mod sinks;
+
+mod headless;
+
+mod cli;
+use clap::Parser;
+use cli::Cli;
+
+mod instance;
+use instance::InstanceLock;
+
+mod schedule;
+use schedule::RefreshSchedule;
+
+#[cfg(all(test, target_os = "linux"))]
+mod test_bus;
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    init_logger()?;
+async fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    let is_headless = cli.headless || cli.command.is_some();
+
+    if is_headless {
+        cli::attach_console();
+    }
 
-    if let Err(e) = body().await {
+    init_logger(is_headless)?;
+
+    let result = match cli.command {
+        Some(command) => cli::run(command).await,
+        None => body(is_headless).await.map(|()| ExitCode::SUCCESS),
+    };
+
+    if let Err(e) = &result {
         tracing::error!("Error: {}", e);
-        return Err(e);
     }
 
-    Ok(())
+    result
 }
 
-async fn body() -> Result<()> {
-    Tray::make_tray_nice();
+async fn body(is_headless: bool) -> Result<()> {
+    let Some(lock) = InstanceLock::acquire()? else {
+        println!("FreeTrayGames is already running");
+        return Ok(());
+    };
+
+    let control = instance::serve(&lock).context("Failed to start control channel")?;
+
+    if !is_headless {
+        Tray::make_tray_nice();
+    }
 
     let mut config = Config::new();
     config.load().await;
 
-    let mut games = Games::fetch().await.context("Failed to initialize games")?;
+    http::init(&config.get_network().await)?;
+
+    let mut schedule = RefreshSchedule::new(config.get_refresh().await)?;
+    let mut games = Games::fetch(&config).await.context("Failed to initialize games")?;
+
+    let claims = Claims::new();
+    claims.load_or_init().await.context("Failed to load claimed offers")?;
 
     let notifications = Notifications::new(
         games.clone(),
+        claims.clone(),
         config.clone(),
+        sinks::enabled_sinks(&config.get_sinks().await, is_headless),
     );
     notifications.load_or_init().await.context("Failed to load notifications")?;
     notifications.push_all_new_games().await.context("Failed to push notifications")?;
 
+    if is_headless {
+        return headless::run(games, claims, config, notifications, schedule, control).await;
+    }
+
     let (tray, rx) = Tray::new(
         games.clone(),
+        claims.clone(),
         config.clone(),
     ).await;
 
+    // Any refresh, manual ones included, restarts the countdown to the next automatic one
+    let refreshed = Arc::new(Notify::new());
+
     let auto_refresh_tx = tray.get_tx().clone();
+    let auto_refresh_games = games.clone();
+    let auto_refresh_done = refreshed.clone();
+    tokio::spawn(async move {
+        loop {
+            let delay = schedule.next_delay(&auto_refresh_games).await;
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {
+                    let _ = auto_refresh_tx.send(Message::Refresh);
+                    auto_refresh_done.notified().await;
+                }
+                _ = auto_refresh_done.notified() => {}
+            }
+        }
+    });
+
+    let control_tx = tray.get_tx().clone();
+    std::thread::spawn(move || {
+        let mut control = control;
+
+        while let Some(message) = control.blocking_recv() {
+            let _ = control_tx.send(message);
+        }
+    });
+
+    let reminders_tx = tray.get_tx().clone();
     tokio::spawn(async move {
         loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(3 * 60 * 60)).await;
-            let _ = auto_refresh_tx.send(Message::Refresh);
+            tokio::time::sleep(tokio::time::Duration::from_secs(15 * 60)).await;
+            let _ = reminders_tx.send(Message::CheckReminders);
         }
     });
 
@@ -73,7 +170,7 @@ async fn body() -> Result<()> {
 
                 for game in &games {
                     if game.id == id {
-                        let target_url = game.open_giveaway_url.clone();
+                        let target_url = game.get_open_url().to_string();
                         let _ = open::that(target_url);
                         break;
                     }
@@ -83,13 +180,45 @@ async fn body() -> Result<()> {
                 let _ = open::that("https://github.com/MrMaxie/free-tray-games");
             }
             Ok(Message::Refresh) => {
-                games.refetch().await?;
-                notifications.push_all_new_games().await?;
+                let is_changed = games.refetch().await?;
+                refreshed.notify_one();
+
+                if is_changed {
+                    notifications.push_all_new_games().await?;
+                    tray.rebuild_tray().await?;
+                } else {
+                    // Countdowns move on and offers end even when the list stays the same
+                    tray.refresh_countdowns().await?;
+                }
+            }
+            Ok(Message::CheckReminders) => {
+                notifications.push_due_reminders().await?;
+                tray.refresh_countdowns().await?;
+            }
+            Ok(Message::MarkClaimed(id)) => {
+                let games = games.get_all().await;
+
+                if let Some(game) = games.iter().find(|g| g.id == id) {
+                    claims.mark_claimed(game).await;
+                    claims.save().await?;
+                    tray.rebuild_tray().await?;
+                }
+            }
+            Ok(Message::UnmarkClaimed(id)) => {
+                claims.unmark_claimed(id).await;
+                claims.save().await?;
                 tray.rebuild_tray().await?;
             }
+            Ok(Message::OpenClaimHistory) => {
+                // Make sure there is something to open even before the first claim
+                claims.save().await?;
+                let _ = open::that(Claims::get_claims_path());
+            }
             Ok(Message::ToggleNotifications) => {
                 config.toggle_notifications().await;
-                config.save().await;
+                if let Err(e) = config.save().await {
+                    tracing::warn!("Notifications toggled for this session only: {:#}", e);
+                }
                 notifications.push_all_new_games().await?;
                 tray.rebuild_tray().await?;
             }
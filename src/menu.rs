@@ -0,0 +1,360 @@
+use std::collections::HashSet;
+use chrono::{DateTime, Local, Utc};
+
+use crate::{
+    config::ClaimedDisplay,
+    games::{format_time_left, Game, GiveawayType, ProviderError},
+    resource::ResourceIcon,
+    tray::Message,
+};
+
+/// Everything the menu depends on, gathered up front so building it doesn't touch any shared state.
+pub struct MenuInput<'a> {
+    pub games: &'a [Game],
+    pub claimed: &'a HashSet<usize>,
+    pub error_reasons: &'a [ProviderError],
+    pub stale_since: Option<DateTime<Utc>>,
+    pub claimed_display: ClaimedDisplay,
+    pub is_notifications_enabled: bool,
+    pub now: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuItem {
+    pub label: String,
+    pub icon: Option<ResourceIcon>,
+    pub action: Message,
+    pub checked: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuEntry {
+    Label(String),
+    Item(MenuItem),
+    Submenu {
+        label: String,
+        icon: Option<ResourceIcon>,
+        items: Vec<MenuItem>,
+    },
+    Separator,
+}
+
+/// Platform-neutral description of the tray menu, rendered by whichever `TrayBackend` is in use.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MenuModel {
+    pub entries: Vec<MenuEntry>,
+}
+
+impl MenuItem {
+    fn new(label: &str, icon: Option<ResourceIcon>, action: Message) -> Self {
+        MenuItem {
+            label: label.to_string(),
+            icon,
+            action,
+            checked: false,
+        }
+    }
+}
+
+impl MenuModel {
+    pub fn build(input: &MenuInput) -> Self {
+        let mut entries = Vec::new();
+
+        if let Some(stale_since) = input.stale_since {
+            entries.push(MenuEntry::Label(format!(
+                "Offline — stale since {}",
+                stale_since.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            )));
+        }
+
+        for error in input.error_reasons {
+            entries.push(MenuEntry::Label(format!("Failed to load from {}: {}", error.provider, error.error)));
+        }
+
+        let (claimed, unclaimed): (Vec<&Game>, Vec<&Game>) = input.games.iter()
+            .partition(|game| input.claimed.contains(&game.id));
+
+        if input.games.is_empty() && input.error_reasons.is_empty() {
+            entries.push(MenuEntry::Label("No active giveaways".to_string()));
+        } else if unclaimed.is_empty() && !claimed.is_empty() {
+            entries.push(MenuEntry::Label("Everything is claimed".to_string()));
+        }
+
+        let mut is_first_section = true;
+
+        for giveaway_type in GiveawayType::ALL {
+            let section: Vec<&Game> = unclaimed.iter()
+                .copied()
+                .filter(|g| g.game_type == giveaway_type)
+                .collect();
+
+            if section.is_empty() {
+                continue;
+            }
+
+            // Games keep the top of the menu, every other type gets its own header
+            if giveaway_type != GiveawayType::Game {
+                if !is_first_section {
+                    entries.push(MenuEntry::Separator);
+                }
+
+                entries.push(MenuEntry::Label(giveaway_type.label().to_string()));
+            }
+
+            is_first_section = false;
+
+            for game in section {
+                entries.push(MenuEntry::Item(Self::game_item(game, input.now)));
+            }
+        }
+
+        if input.claimed_display == ClaimedDisplay::Collapse && !claimed.is_empty() {
+            entries.push(MenuEntry::Submenu {
+                label: format!("Claimed ({})", claimed.len()),
+                icon: None,
+                items: claimed.iter().map(|game| Self::game_item(game, input.now)).collect(),
+            });
+        }
+
+        entries.push(MenuEntry::Separator);
+
+        if !input.games.is_empty() {
+            let items = input.games.iter()
+                .map(|game| {
+                    let is_claimed = input.claimed.contains(&game.id);
+                    let action = if is_claimed {
+                        Message::UnmarkClaimed(game.id)
+                    } else {
+                        Message::MarkClaimed(game.id)
+                    };
+
+                    MenuItem {
+                        checked: is_claimed,
+                        ..MenuItem::new(&game.title, None, action)
+                    }
+                })
+                .collect();
+
+            entries.push(MenuEntry::Submenu {
+                label: "Mark as Claimed".to_string(),
+                icon: None,
+                items,
+            });
+        }
+
+        let notifications_icon = if input.is_notifications_enabled {
+            ResourceIcon::NotificationsEnabled
+        } else {
+            ResourceIcon::NotificationsDisabled
+        };
+
+        entries.extend([
+            MenuEntry::Item(MenuItem::new("Claim History", None, Message::OpenClaimHistory)),
+            MenuEntry::Item(MenuItem::new("Refresh", Some(ResourceIcon::Refresh), Message::Refresh)),
+            MenuEntry::Item(MenuItem::new("Homepage", Some(ResourceIcon::BrandGithub), Message::OpenProjectHomepage)),
+            MenuEntry::Item(MenuItem::new("Toggle Notifications", Some(notifications_icon), Message::ToggleNotifications)),
+            MenuEntry::Separator,
+            MenuEntry::Item(MenuItem::new("Quit", None, Message::Quit)),
+        ]);
+
+        MenuModel { entries }
+    }
+
+    pub fn get_game_label(game: &Game, now: DateTime<Utc>) -> String {
+        let label = match game.time_left(now) {
+            Some(time_left) => format!("{} — ends in {}", game.title, format_time_left(time_left)),
+            None => game.title.clone(),
+        };
+
+        match game.stale_since {
+            Some(_) => format!("{} (stale)", label),
+            None => label,
+        }
+    }
+
+    fn game_item(game: &Game, now: DateTime<Utc>) -> MenuItem {
+        MenuItem::new(
+            &Self::get_game_label(game, now),
+            Some(game.platform.icon()),
+            Message::OpenGame(game.id),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::{platform::Platform, providers::FetchError};
+
+    fn now() -> DateTime<Utc> {
+        "2025-10-01T12:00:00Z".parse().unwrap()
+    }
+
+    fn game(id: usize, title: &str, platform: Platform, game_type: GiveawayType, ends_in: Option<Duration>) -> Game {
+        Game {
+            id,
+            title: title.to_string(),
+            platform,
+            game_type,
+            end_date: ends_in.map(|ends_in| now() + ends_in),
+            ..Default::default()
+        }
+    }
+
+    fn input<'a>(games: &'a [Game], claimed: &'a HashSet<usize>, error_reasons: &'a [ProviderError]) -> MenuInput<'a> {
+        MenuInput {
+            games,
+            claimed,
+            error_reasons,
+            stale_since: None,
+            claimed_display: ClaimedDisplay::Collapse,
+            is_notifications_enabled: true,
+            now: now(),
+        }
+    }
+
+    /// One line per entry, so a whole menu can be compared at a glance.
+    fn render(model: &MenuModel) -> String {
+        fn item(item: &MenuItem) -> String {
+            let check = if item.checked { "[x] " } else { "" };
+
+            match item.icon {
+                Some(icon) => format!("{}{} ({:?}) -> {:?}", check, item.label, icon, item.action),
+                None => format!("{}{} -> {:?}", check, item.label, item.action),
+            }
+        }
+
+        let mut lines = Vec::new();
+
+        for entry in &model.entries {
+            match entry {
+                MenuEntry::Label(label) => lines.push(format!("# {}", label)),
+                MenuEntry::Item(menu_item) => lines.push(item(menu_item)),
+                MenuEntry::Submenu { label, items, .. } => {
+                    lines.push(format!("> {}", label));
+                    lines.extend(items.iter().map(|menu_item| format!("  {}", item(menu_item))));
+                }
+                MenuEntry::Separator => lines.push("---".to_string()),
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    #[test]
+    fn groups_offers_by_type_and_collapses_claimed() {
+        let games = [
+            game(1, "Hollow Lantern", Platform::Epic, GiveawayType::Game, Some(Duration::hours(51))),
+            game(2, "Lantern Skins", Platform::Epic, GiveawayType::Dlc, None),
+            game(3, "Orbital Gardener", Platform::Steam, GiveawayType::Game, Some(Duration::minutes(40))),
+            game(4, "Starfall Tactics", Platform::Gog, GiveawayType::Game, None),
+        ];
+        let claimed = HashSet::from([4]);
+        let errors = [ProviderError {
+            provider: "gog",
+            error: FetchError::Timeout { url: "https://catalog.gog.com".to_string() },
+        }];
+
+        let model = MenuModel::build(&input(&games, &claimed, &errors));
+
+        assert_eq!(render(&model), "\
+# Failed to load from gog: Request timed out
+Hollow Lantern — ends in 2d 3h (BrandEpic) -> OpenGame(1)
+Orbital Gardener — ends in 40m (BrandSteam) -> OpenGame(3)
+---
+# DLC
+Lantern Skins (BrandEpic) -> OpenGame(2)
+> Claimed (1)
+  Starfall Tactics (BrandGog) -> OpenGame(4)
+---
+> Mark as Claimed
+  Hollow Lantern -> MarkClaimed(1)
+  Lantern Skins -> MarkClaimed(2)
+  Orbital Gardener -> MarkClaimed(3)
+  [x] Starfall Tactics -> UnmarkClaimed(4)
+Claim History -> OpenClaimHistory
+Refresh (Refresh) -> Refresh
+Homepage (BrandGithub) -> OpenProjectHomepage
+Toggle Notifications (NotificationsEnabled) -> ToggleNotifications
+---
+Quit -> Quit");
+    }
+
+    #[test]
+    fn says_so_when_there_is_nothing_to_show() {
+        let claimed = HashSet::new();
+        let model = MenuModel::build(&MenuInput {
+            is_notifications_enabled: false,
+            ..input(&[], &claimed, &[])
+        });
+
+        assert_eq!(render(&model), "\
+# No active giveaways
+---
+Claim History -> OpenClaimHistory
+Refresh (Refresh) -> Refresh
+Homepage (BrandGithub) -> OpenProjectHomepage
+Toggle Notifications (NotificationsDisabled) -> ToggleNotifications
+---
+Quit -> Quit");
+    }
+
+    #[test]
+    fn shows_why_nothing_loaded_instead_of_an_empty_list() {
+        let claimed = HashSet::new();
+        let errors = [
+            ProviderError {
+                provider: "epic",
+                error: FetchError::Network { url: "https://store.example".to_string(), cause: "dns error".to_string() },
+            },
+            ProviderError {
+                provider: "gamerpower",
+                error: FetchError::Timeout { url: "https://www.gamerpower.com".to_string() },
+            },
+        ];
+
+        let model = MenuModel::build(&input(&[], &claimed, &errors));
+        let rendered = render(&model);
+
+        assert!(!rendered.contains("No active giveaways"), "{}", rendered);
+        assert_eq!(rendered.lines().take(3).collect::<Vec<_>>(), [
+            "# Failed to load from epic: Network unavailable",
+            "# Failed to load from gamerpower: Request timed out",
+            "---",
+        ]);
+    }
+
+    #[test]
+    fn hides_claimed_offers_and_marks_stale_ones() {
+        let mut stale = game(2, "Starfall Tactics", Platform::Gog, GiveawayType::Game, None);
+        stale.stale_since = Some(now() - Duration::hours(3));
+
+        let games = [game(1, "Hollow Lantern", Platform::Epic, GiveawayType::Game, None), stale];
+        let claimed = HashSet::from([1]);
+
+        let model = MenuModel::build(&MenuInput {
+            claimed_display: ClaimedDisplay::Hide,
+            ..input(&games, &claimed, &[])
+        });
+
+        assert_eq!(render(&model).lines().take(5).collect::<Vec<_>>(), [
+            "Starfall Tactics (stale) (BrandGog) -> OpenGame(2)",
+            "---",
+            "> Mark as Claimed",
+            "  [x] Hollow Lantern -> UnmarkClaimed(1)",
+            "  Starfall Tactics -> MarkClaimed(2)",
+        ]);
+    }
+
+    #[test]
+    fn says_everything_is_claimed() {
+        let games = [game(1, "Hollow Lantern", Platform::Epic, GiveawayType::Game, None)];
+        let claimed = HashSet::from([1]);
+
+        let model = MenuModel::build(&input(&games, &claimed, &[]));
+
+        assert_eq!(model.entries[0], MenuEntry::Label("Everything is claimed".to_string()));
+    }
+}
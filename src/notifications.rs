@@ -1,35 +1,56 @@
-use std::{collections::HashMap, env, fs::{create_dir_all, File}, io::{BufReader, BufWriter, Write}, path::PathBuf, sync::Arc};
+use std::{collections::HashMap, env, fs::File, io::{BufReader, BufWriter, Write}, path::PathBuf, sync::Arc};
 use chrono::{DateTime, Duration, Utc};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use anyhow::{Result, Context};
 
-use crate::notify_body::WinToastNotify;
-use crate::{config::Config, games::{Game, Games}};
+use crate::claims::Claims;
+use crate::sinks::{Notification, NotificationSink};
+use crate::{config::Config, games::{format_time_left, Game, Games, GiveawayType}};
 
 type NotifiedMap = HashMap<usize, DateTime<Utc>>;
 
+#[derive(Default, Deserialize, Serialize)]
+struct NotificationsLog {
+    notified: HashMap<usize, i64>,
+    #[serde(default)]
+    reminded: HashMap<usize, i64>,
+}
+
 #[derive(Clone)]
 pub struct Notifications {
     games: Games,
+    claims: Claims,
     config: Config,
+    sinks: Arc<Vec<Box<dyn NotificationSink>>>,
     notified: Arc<Mutex<NotifiedMap>>,
+    reminded: Arc<Mutex<NotifiedMap>>,
 }
 
 impl Notifications {
     pub fn new(
         games: Games,
+        claims: Claims,
         config: Config,
+        sinks: Vec<Box<dyn NotificationSink>>,
     ) -> Self {
         Notifications {
             games,
+            claims,
             config,
+            sinks: Arc::new(sinks),
             notified: Arc::new(Mutex::new(HashMap::new())),
+            reminded: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
     pub async fn clear_notified(&self) {
         let mut notified = self.notified.lock().await;
         *notified = HashMap::new();
+
+        let mut reminded = self.reminded.lock().await;
+        *reminded = HashMap::new();
     }
 
     pub async fn push_all_new_games(&self) -> Result<()> {
@@ -57,26 +78,123 @@ impl Notifications {
             return Ok(());
         }
 
+        if game.is_silenced || self.claims.is_claimed(game.id).await {
+            return Ok(());
+        }
+
         let mut notified = self.notified.lock().await;
         if notified.contains_key(&game.id) {
             return Ok(());
         }
 
         notified.insert(game.id, Utc::now());
+        drop(notified);
+
+        // The first notification already tells when the offer ends
+        if self.is_ending_soon(&game).await {
+            self.reminded.lock().await.insert(game.id, Utc::now());
+        }
+
+        let mut details = Vec::new();
+
+        if let Some(worth) = game.worth {
+            details.push(format!("Worth ${:.2}", worth));
+        }
+
+        if let Some(end_date) = game.end_date {
+            details.push(format!("free until {}", end_date.format("%b %-d, %H:%M UTC")));
+        }
+
+        if game.users > 0 {
+            details.push(format!("{} claimed", game.users));
+        }
+
+        let message = if details.is_empty() {
+            "Click to claim".to_string()
+        } else {
+            format!("{}. Click to claim", details.join(", "))
+        };
+
+        self.dispatch(Notification {
+            title: Self::get_title(&game),
+            message,
+            game,
+        }).await;
+
+        Ok(())
+    }
+
+    pub async fn push_due_reminders(&self) -> Result<()> {
+        let is_notfications_enabled = self.config.is_notifications_enabled().await;
+
+        if !is_notfications_enabled {
+            return Ok(());
+        }
+
+        let games = self.games.get_all().await;
+        let now = Utc::now();
+
+        for game in games {
+            if game.is_silenced || !self.is_ending_soon(&game).await {
+                continue;
+            }
+
+            if self.claims.is_claimed(game.id).await {
+                continue;
+            }
+
+            let mut reminded = self.reminded.lock().await;
+            if reminded.contains_key(&game.id) {
+                continue;
+            }
 
-        let image_path = Self::download_image(&game.image).await.context("Failed to download image")?;
+            reminded.insert(game.id, now);
+            drop(reminded);
 
-        WinToastNotify::new(Config::get_app_id().as_str())
-            .set_title(format!("{} ({})", game.title.as_str(), game.platform.as_str()).as_str())
-            .set_messages(vec!["Click to claim"])
-            .set_image(&image_path)
-            .set_open(game.open_giveaway_url.as_str())
-            .show()
-            .expect("Failed to show notification");
+            let message = match game.time_left(now) {
+                Some(time_left) => format!("Ends in {}. Click to claim", format_time_left(time_left)),
+                None => "Ending soon. Click to claim".to_string(),
+            };
+
+            let title = format!("Ending soon: {}", Self::get_title(&game));
+
+            self.dispatch(Notification { title, message, game }).await;
+        }
+
+        self.save().await.context("Failed to save notification state")?;
 
         Ok(())
     }
 
+    async fn is_ending_soon(&self, game: &Game) -> bool {
+        let Some(reminder_before) = self.config.get_reminder_before().await else {
+            return false;
+        };
+
+        match game.time_left(Utc::now()) {
+            Some(time_left) => time_left <= reminder_before,
+            None => false,
+        }
+    }
+
+    fn get_title(game: &Game) -> String {
+        match game.game_type {
+            GiveawayType::Game => format!("{} ({})", game.title, game.platform.as_str()),
+            game_type => format!("[{}] {} ({})", game_type.label(), game.title, game.platform.as_str()),
+        }
+    }
+
+    /// Sends the notification everywhere at once, one failing or retrying sink doesn't hold up the others.
+    async fn dispatch(&self, notification: Notification) {
+        let results = join_all(self.sinks.iter().map(|sink| sink.send(&notification))).await;
+
+        for (sink, result) in self.sinks.iter().zip(results) {
+            if let Err(e) = result {
+                tracing::error!("Failed to notify through {}: {:#}", sink.name(), e);
+            }
+        }
+    }
+
     pub async fn load_or_init(&self) -> Result<()> {
         let path = Self::get_notifications_log_path();
 
@@ -94,22 +212,43 @@ impl Notifications {
         };
 
         let reader = BufReader::new(file);
-        let raw : HashMap<usize, i64> = serde_json::from_reader(reader).unwrap_or_default();
-        let raw: NotifiedMap = raw.into_iter()
-            .map(|(id, ts)| (id, DateTime::<Utc>::from_timestamp(ts, 0).unwrap()))
-            .collect();
-
-        let week_ago = Utc::now() - Duration::days(7);
-        let new_raw = raw.into_iter()
-            .filter(|(_, ts)| *ts > week_ago)
-            .collect();
+        let stored: serde_json::Value = serde_json::from_reader(reader).unwrap_or_default();
+
+        // Before reminders existed the log was a bare id to timestamp map. Untagged enums
+        // can't read the numeric keys, so the shape is checked by hand.
+        let log = if stored.get("notified").is_some() {
+            serde_json::from_value(stored).unwrap_or_default()
+        } else {
+            NotificationsLog {
+                notified: serde_json::from_value(stored).unwrap_or_default(),
+                reminded: HashMap::new(),
+            }
+        };
 
         let mut notified = self.notified.lock().await;
-        *notified = new_raw;
+        *notified = Self::from_timestamps(log.notified);
+
+        let mut reminded = self.reminded.lock().await;
+        *reminded = Self::from_timestamps(log.reminded);
 
         Ok(())
     }
 
+    fn from_timestamps(raw: HashMap<usize, i64>) -> NotifiedMap {
+        let week_ago = Utc::now() - Duration::days(7);
+
+        raw.into_iter()
+            .filter_map(|(id, ts)| DateTime::<Utc>::from_timestamp(ts, 0).map(|dt| (id, dt)))
+            .filter(|(_, ts)| *ts > week_ago)
+            .collect()
+    }
+
+    fn to_timestamps(map: &NotifiedMap) -> HashMap<usize, i64> {
+        map.iter()
+            .map(|(id, dt)| (*id, dt.timestamp()))
+            .collect()
+    }
+
     pub fn get_notifications_log_path() -> PathBuf {
         let mut exe_path = env::current_exe().unwrap();
         exe_path.set_file_name("notifications.log");
@@ -117,49 +256,66 @@ impl Notifications {
         exe_path
     }
 
-    async fn download_image(url: &str) -> Result<String> {
-        let mut temp_dir = env::temp_dir();
-        temp_dir.push("free_tray_games");
+    pub async fn save(&self) -> Result<()> {
+        let path = Self::get_notifications_log_path();
+        let file = File::create(&path).expect("cannot write notified cache");
+        let mut writer = BufWriter::new(file);
 
-        create_dir_all(temp_dir.clone()).context("Failed to create temp directory for images")?;
+        let log = NotificationsLog {
+            notified: Self::to_timestamps(&*self.notified.lock().await),
+            reminded: Self::to_timestamps(&*self.reminded.lock().await),
+        };
 
-        let hash_name = format!("{:x}", md5::compute(url));
+        let json = serde_json::to_string(&log).expect("notifications log serialization failed");
+        writer.write_all(json.as_bytes()).expect("notifications log write failed");
 
-        let ext = url.split('.').last().unwrap_or("jpg");
-        let filename = format!("{}.{}", hash_name, ext);
+        Ok(())
+    }
+}
 
-        let mut file_path = temp_dir.clone();
-        file_path.push(filename);
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use tokio::{sync::Barrier, time::timeout};
 
-        if file_path.exists() {
-            return Ok(file_path.to_str().unwrap().to_string());
-        }
+    use super::*;
+    use crate::{http::HttpCache, sinks::tests::notification};
 
-        let response = reqwest::get(url).await.context("Failed to download image")?;
-        if !response.status().is_success() {
-            return Ok("".to_string());
-        }
+    /// Only returns once every other sink is sending too.
+    struct WaitingSink {
+        barrier: Arc<Barrier>,
+    }
 
-        let content = response.bytes().await.context("Failed to read image content")?;
-        let mut file = File::create(file_path.clone()).context("Failed to create image file")?;
-        std::io::copy(&mut content.as_ref(), &mut file).context("Failed to write image content")?;
+    #[async_trait]
+    impl NotificationSink for WaitingSink {
+        fn name(&self) -> &'static str {
+            "waiting"
+        }
 
-        Ok(file_path.to_str().unwrap().to_string())
+        async fn send(&self, _notification: &Notification) -> Result<()> {
+            self.barrier.wait().await;
+            Ok(())
+        }
     }
 
-    pub async fn save(&self) -> Result<()> {
-        let path = Self::get_notifications_log_path();
-        let file = File::create(&path).expect("cannot write notified cache");
-        let mut writer = BufWriter::new(file);
-
-        let map = self.notified.lock().await.clone();
-        let timestamp_map: HashMap<usize, i64> = map.iter()
-            .map(|(id, dt)| (*id, dt.timestamp()))
-            .collect();
+    #[tokio::test]
+    async fn sends_to_every_sink_at_once() {
+        let config = Config::new();
+        let barrier = Arc::new(Barrier::new(2));
+        let sinks: Vec<Box<dyn NotificationSink>> = vec![
+            Box::new(WaitingSink { barrier: barrier.clone() }),
+            Box::new(WaitingSink { barrier }),
+        ];
+
+        let notifications = Notifications::new(
+            Games::new(Vec::new(), config.clone(), Arc::new(HttpCache::new())),
+            Claims::new(),
+            config,
+            sinks,
+        );
 
-        let json = serde_json::to_string(&timestamp_map).expect("notifications log serialization failed");
-        writer.write_all(json.as_bytes()).expect("notifications log write failed");
+        let sent = timeout(std::time::Duration::from_secs(5), notifications.push_notification(notification().game)).await;
 
-        Ok(())
+        assert!(matches!(sent, Ok(Ok(()))), "sinks were sent to one after another");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+use crate::resource::ResourceIcon;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Steam,
+    Epic,
+    Gog,
+    Itch,
+    Ubisoft,
+    Ea,
+    Battlenet,
+    Prime,
+    Humble,
+    Indiegala,
+    #[default]
+    Other,
+}
+
+impl Platform {
+    pub const ALL: [Platform; 11] = [
+        Platform::Steam,
+        Platform::Epic,
+        Platform::Gog,
+        Platform::Itch,
+        Platform::Ubisoft,
+        Platform::Ea,
+        Platform::Battlenet,
+        Platform::Prime,
+        Platform::Humble,
+        Platform::Indiegala,
+        Platform::Other,
+    ];
+
+    /// Reads gamerpower's `platforms` list, e.g. "PC, Epic Games Store". Only whole
+    /// entries are compared, so "PC, DRM-Free" gives nothing.
+    pub fn from_store_list(list: &str) -> Option<Platform> {
+        list.split(',')
+            .map(|entry| entry.trim().to_lowercase())
+            .find_map(|entry| {
+                Platform::ALL
+                    .into_iter()
+                    .find(|platform| platform.keywords().contains(&entry.as_str()))
+            })
+    }
+
+    /// Finds the storefront mentioned in a free-form text such as an offer title.
+    /// Store names only match as whole words, so "SteamWorld" isn't Steam.
+    /// Stores are checked in order, so the first match wins.
+    pub fn classify(text: &str) -> Option<Platform> {
+        let text = text.to_lowercase();
+
+        Platform::ALL
+            .into_iter()
+            .find(|platform| platform.keywords().iter().any(|k| contains_word(&text, k)))
+    }
+
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Platform::Steam => &["steam"],
+            Platform::Epic => &["epic games store", "epic games"],
+            Platform::Gog => &["gog", "gog.com"],
+            Platform::Itch => &["itch.io", "itchio"],
+            Platform::Ubisoft => &["ubisoft", "ubisoft connect", "uplay"],
+            Platform::Ea => &["ea app", "origin", "electronic arts"],
+            Platform::Battlenet => &["battle.net", "battlenet"],
+            Platform::Prime => &["prime gaming", "amazon games"],
+            Platform::Humble => &["humble bundle", "humble store", "humble choice"],
+            Platform::Indiegala => &["indiegala"],
+            Platform::Other => &[],
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::Steam => "steam",
+            Platform::Epic => "epic",
+            Platform::Gog => "gog",
+            Platform::Itch => "itch",
+            Platform::Ubisoft => "ubisoft",
+            Platform::Ea => "ea",
+            Platform::Battlenet => "battlenet",
+            Platform::Prime => "prime",
+            Platform::Humble => "humble",
+            Platform::Indiegala => "indiegala",
+            Platform::Other => "other",
+        }
+    }
+
+    /// Store name as shown to people, e.g. in chat messages.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Platform::Steam => "Steam",
+            Platform::Epic => "Epic Games Store",
+            Platform::Gog => "GOG",
+            Platform::Itch => "itch.io",
+            Platform::Ubisoft => "Ubisoft Connect",
+            Platform::Ea => "EA app",
+            Platform::Battlenet => "Battle.net",
+            Platform::Prime => "Prime Gaming",
+            Platform::Humble => "Humble",
+            Platform::Indiegala => "IndieGala",
+            Platform::Other => "Other",
+        }
+    }
+
+    pub fn icon(&self) -> ResourceIcon {
+        match self {
+            Platform::Steam => ResourceIcon::BrandSteam,
+            Platform::Epic => ResourceIcon::BrandEpic,
+            Platform::Gog => ResourceIcon::BrandGog,
+            Platform::Itch => ResourceIcon::BrandItch,
+            Platform::Ubisoft => ResourceIcon::BrandUbisoft,
+            Platform::Ea => ResourceIcon::BrandEa,
+            Platform::Battlenet => ResourceIcon::BrandBattlenet,
+            Platform::Prime => ResourceIcon::BrandPrime,
+            Platform::Humble => ResourceIcon::BrandHumble,
+            Platform::Indiegala => ResourceIcon::BrandIndiegala,
+            Platform::Other => ResourceIcon::BrandGeneric,
+        }
+    }
+}
+
+/// Whether `word` shows up in `text` without letters or digits glued to either side.
+fn contains_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_store_lists() {
+        let cases = [
+            ("PC, Epic Games Store", Some(Platform::Epic)),
+            ("PC, Steam", Some(Platform::Steam)),
+            ("PC, GOG, Steam", Some(Platform::Gog)),
+            ("PC, DRM-Free, Itch.io", Some(Platform::Itch)),
+            ("PC, DRM-Free", None),
+            ("PC, Steam Deck", None),
+            ("", None),
+        ];
+
+        for (list, expected) in cases {
+            assert_eq!(Platform::from_store_list(list), expected, "{}", list);
+        }
+    }
+
+    #[test]
+    fn classifies_whole_store_names_only() {
+        let cases = [
+            ("Orbital Gardener (Steam) Giveaway", Some(Platform::Steam)),
+            ("SteamWorld Dig (GOG) Giveaway", Some(Platform::Gog)),
+            ("SteamWorld Dig 2", None),
+            ("Hollow Lantern Free on Epic Games", Some(Platform::Epic)),
+            ("Prime Gaming: Ashen Loot Pack", Some(Platform::Prime)),
+            ("Originals Collection", None),
+            ("Indiegala Freebie Weekend", Some(Platform::Indiegala)),
+            ("Starfall Tactics", None),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(Platform::classify(text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn prefers_stores_in_their_order_when_the_text_names_several() {
+        // Steam comes before GOG in `Platform::ALL`, wherever it shows up in the text
+        assert_eq!(Platform::classify("Steam key, also on GOG"), Some(Platform::Steam));
+        assert_eq!(Platform::classify("GOG copy, Steam key inside"), Some(Platform::Steam));
+    }
+
+    #[test]
+    fn falls_back_to_other() {
+        let platform = Platform::from_store_list("PC, DRM-Free")
+            .or_else(|| Platform::classify("Starfall Tactics Giveaway"))
+            .unwrap_or_default();
+
+        assert_eq!(platform, Platform::Other);
+        assert_eq!(platform.icon(), ResourceIcon::BrandGeneric);
+    }
+
+    #[test]
+    fn matches_words_with_punctuation_around_them() {
+        assert!(contains_word("free on steam!", "steam"));
+        assert!(contains_word("(gog.com)", "gog.com"));
+        assert!(!contains_word("steamworld", "steam"));
+        assert!(!contains_word("megagog", "gog"));
+    }
+}
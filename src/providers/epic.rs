@@ -0,0 +1,362 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{get_json, FetchError, GiveawayProvider, HealthTracker, ProviderHealth};
+use crate::{games::{Game, GiveawayType}, http::HttpCache, platform::Platform};
+
+const DEFAULT_BASE_URL: &str = "https://store-site-backend-static-ipv4.ak.epicgames.com";
+const STORE_URL: &str = "https://store.epicgames.com/en-US";
+
+#[derive(Debug, Deserialize)]
+struct PromotionsResponse {
+    data: PromotionsData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PromotionsData {
+    catalog: Catalog,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Catalog {
+    search_store: SearchStore,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchStore {
+    elements: Vec<Element>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Element {
+    id: String,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    offer_type: Option<String>,
+    #[serde(default)]
+    product_slug: Option<String>,
+    #[serde(default)]
+    url_slug: Option<String>,
+    #[serde(default)]
+    key_images: Vec<KeyImage>,
+    #[serde(default)]
+    offer_mappings: Option<Vec<PageMapping>>,
+    #[serde(default)]
+    catalog_ns: Option<CatalogNs>,
+    #[serde(default)]
+    price: Option<Price>,
+    #[serde(default)]
+    promotions: Option<Promotions>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Price {
+    total_price: TotalPrice,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TotalPrice {
+    original_price: u64,
+    #[serde(default)]
+    currency_info: Option<CurrencyInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CurrencyInfo {
+    decimals: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyImage {
+    #[serde(rename = "type")]
+    image_type: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageMapping {
+    page_slug: String,
+    page_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CatalogNs {
+    #[serde(default)]
+    mappings: Option<Vec<PageMapping>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Promotions {
+    #[serde(default)]
+    promotional_offers: Vec<PromotionalOfferGroup>,
+    #[serde(default)]
+    upcoming_promotional_offers: Vec<PromotionalOfferGroup>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromotionalOfferGroup {
+    promotional_offers: Vec<PromotionalOffer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromotionalOffer {
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    discount_setting: DiscountSetting,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiscountSetting {
+    discount_percentage: u32,
+}
+
+impl PromotionalOffer {
+    fn is_free_at(&self, now: DateTime<Utc>) -> bool {
+        // Epic expresses the price left after discount, so 0 means 100% off
+        self.discount_setting.discount_percentage == 0
+            && self.start_date <= now
+            && now < self.end_date
+    }
+}
+
+impl Element {
+    fn free_offer_at(&self, now: DateTime<Utc>) -> Option<&PromotionalOffer> {
+        let promotions = self.promotions.as_ref()?;
+
+        // Upcoming offers are included as the static backend keeps serving
+        // them there for a while after they started
+        promotions.promotional_offers.iter()
+            .chain(promotions.upcoming_promotional_offers.iter())
+            .flat_map(|group| group.promotional_offers.iter())
+            .find(|offer| offer.is_free_at(now))
+    }
+
+    fn page_slug(&self) -> Option<&str> {
+        let mappings = self.offer_mappings.iter()
+            .chain(self.catalog_ns.iter().filter_map(|ns| ns.mappings.as_ref()))
+            .flatten();
+
+        for mapping in mappings {
+            if mapping.page_type == "productHome" && !mapping.page_slug.is_empty() {
+                return Some(mapping.page_slug.as_str());
+            }
+        }
+
+        [&self.product_slug, &self.url_slug]
+            .into_iter()
+            .flatten()
+            .map(|slug| slug.trim_end_matches("/home"))
+            .find(|slug| !slug.is_empty() && *slug != "[]")
+    }
+
+    fn image(&self) -> String {
+        ["OfferImageWide", "DieselStoreFrontWide", "Thumbnail", "OfferImageTall"]
+            .iter()
+            .find_map(|wanted| self.key_images.iter().find(|i| i.image_type == *wanted))
+            .or(self.key_images.first())
+            .map(|i| i.url.clone())
+            .unwrap_or_default()
+    }
+
+    fn thumbnail(&self) -> Option<String> {
+        self.key_images.iter()
+            .find(|i| i.image_type == "Thumbnail")
+            .map(|i| i.url.clone())
+    }
+
+    fn giveaway_type(&self) -> GiveawayType {
+        match self.offer_type.as_deref() {
+            Some("DLC") | Some("ADD_ON") => GiveawayType::Dlc,
+            _ => GiveawayType::Game,
+        }
+    }
+
+    fn worth(&self) -> Option<f64> {
+        let total = &self.price.as_ref()?.total_price;
+        let decimals = total.currency_info.as_ref().map(|c| c.decimals).unwrap_or(2);
+
+        match total.original_price {
+            0 => None,
+            price => Some(price as f64 / 10f64.powi(decimals as i32)),
+        }
+    }
+
+    fn into_game(self, now: DateTime<Utc>) -> Game {
+        let url = match self.page_slug() {
+            Some(slug) => format!("{}/p/{}", STORE_URL, slug),
+            // Mystery games have no product page until they are revealed
+            None => format!("{}/free-games", STORE_URL),
+        };
+
+        let game_type = self.giveaway_type();
+        let worth = self.worth();
+        let offer = self.free_offer_at(now);
+        let published_date = offer.map(|o| o.start_date);
+        let end_date = offer.map(|o| o.end_date);
+        let thumbnail = self.thumbnail();
+
+        Game {
+            id: Game::synthetic_id("epic", &self.id),
+            image: self.image(),
+            title: self.title,
+            platforms: "Epic Games Store".to_string(),
+            platform: Platform::Epic,
+            open_giveaway_url: url,
+            game_type,
+            status: "Active".to_string(),
+            worth,
+            end_date,
+            published_date,
+            thumbnail,
+            description: self.description.filter(|d| !d.trim().is_empty()),
+            ..Default::default()
+        }
+    }
+}
+
+pub struct EpicProvider {
+    base_url: String,
+    http: Arc<HttpCache>,
+    health: HealthTracker,
+}
+
+impl EpicProvider {
+    pub fn new(http: Arc<HttpCache>) -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL, http)
+    }
+
+    pub fn with_base_url(base_url: &str, http: Arc<HttpCache>) -> Self {
+        EpicProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+            health: HealthTracker::new(),
+        }
+    }
+
+    async fn fetch_games(&self) -> Result<Vec<Game>, FetchError> {
+        let url = format!(
+            "{}/freeGamesPromotions?locale=en-US&country=US&allowCountries=US",
+            self.base_url,
+        );
+
+        let promotions: PromotionsResponse = get_json(&self.http, &url).await?;
+
+        Ok(Self::consume_promotions(promotions, Utc::now()))
+    }
+
+    fn consume_promotions(promotions: PromotionsResponse, now: DateTime<Utc>) -> Vec<Game> {
+        promotions.data.catalog.search_store.elements
+            .into_iter()
+            .filter(|e| e.free_offer_at(now).is_some())
+            .map(|e| e.into_game(now))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl GiveawayProvider for EpicProvider {
+    fn name(&self) -> &'static str {
+        "epic"
+    }
+
+    async fn fetch(&self) -> Result<Vec<Game>, FetchError> {
+        let result = self.fetch_games().await;
+        self.health.track(result).await
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        self.health.get().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    const PROMOTIONS: &str = include_str!("../../tests/fixtures/epic_promotions.json");
+
+    async fn provider_serving(response: ResponseTemplate) -> (MockServer, EpicProvider) {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/freeGamesPromotions"))
+            .and(query_param("country", "US"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+
+        let provider = EpicProvider::with_base_url(&server.uri(), Arc::new(HttpCache::new()));
+
+        (server, provider)
+    }
+
+    #[tokio::test]
+    async fn parses_current_free_promotions() {
+        let (_server, provider) = provider_serving(ResponseTemplate::new(200).set_body_string(PROMOTIONS)).await;
+
+        let games = provider.fetch().await.unwrap();
+        let titles: Vec<&str> = games.iter().map(|g| g.title.as_str()).collect();
+
+        assert_eq!(titles, ["Hollow Lantern", "Lantern Skins Pack", "Mystery Game"]);
+
+        let game = &games[0];
+        assert_eq!(game.id, Game::synthetic_id("epic", "a1b2c3"));
+        assert_eq!(game.platform, Platform::Epic);
+        assert_eq!(game.game_type, GiveawayType::Game);
+        assert_eq!(game.open_giveaway_url, "https://store.epicgames.com/en-US/p/hollow-lantern-5f1a");
+        assert_eq!(game.image, "https://cdn.example/hollow-wide.jpg");
+        assert_eq!(game.thumbnail.as_deref(), Some("https://cdn.example/hollow-thumb.jpg"));
+        assert_eq!(game.worth, Some(24.99));
+        assert_eq!(game.end_date, "2099-01-09T16:00:00Z".parse().ok());
+        assert_eq!(game.description.as_deref(), Some("A lantern-lit roguelike."));
+
+        let dlc = &games[1];
+        assert_eq!(dlc.game_type, GiveawayType::Dlc);
+        assert_eq!(dlc.open_giveaway_url, "https://store.epicgames.com/en-US/p/lantern-skins-pack");
+        assert_eq!(dlc.image, "https://cdn.example/skins-tall.jpg");
+        assert_eq!(dlc.worth, None);
+        assert_eq!(dlc.description, None);
+
+        assert_eq!(games[2].open_giveaway_url, "https://store.epicgames.com/en-US/free-games");
+        assert!(matches!(provider.health().await, ProviderHealth::Healthy));
+    }
+
+    #[tokio::test]
+    async fn names_the_field_that_did_not_match() {
+        let (_server, provider) = provider_serving(ResponseTemplate::new(200).set_body_string(r#"{"data": {"Catalog": {}}}"#)).await;
+
+        match provider.fetch().await {
+            Err(FetchError::Decode { field, .. }) => assert_eq!(field.as_deref(), Some("data.Catalog")),
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+
+        assert!(matches!(provider.health().await, ProviderHealth::Failing { .. }));
+    }
+
+    #[tokio::test]
+    async fn reports_server_errors() {
+        let (_server, provider) = provider_serving(ResponseTemplate::new(503)).await;
+
+        assert!(matches!(provider.fetch().await, Err(FetchError::Http { status: StatusCode::SERVICE_UNAVAILABLE, .. })));
+    }
+}
@@ -0,0 +1,151 @@
+use std::{error::Error, fmt, time::Duration};
+use chrono::{DateTime, Utc};
+use reqwest::{header::RETRY_AFTER, Response, StatusCode};
+
+/// Why a provider couldn't deliver its offers. `Display` is short enough for the tray,
+/// `detail` adds the URL and underlying cause for the log.
+#[derive(Debug, Clone)]
+pub enum FetchError {
+    Network {
+        url: String,
+        cause: String,
+    },
+    Timeout {
+        url: String,
+    },
+    Http {
+        url: String,
+        status: StatusCode,
+    },
+    RateLimited {
+        url: String,
+        retry_after: Option<Duration>,
+    },
+    Decode {
+        url: String,
+        /// Path of the value that didn't match, e.g. `data.Catalog.searchStore`.
+        field: Option<String>,
+        cause: String,
+    },
+}
+
+impl FetchError {
+    pub fn from_reqwest(url: &str, error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            FetchError::Timeout { url: url.to_string() }
+        } else {
+            FetchError::Network {
+                url: url.to_string(),
+                cause: describe_cause(&error.without_url()),
+            }
+        }
+    }
+
+    pub fn from_status(url: &str, response: &Response) -> Self {
+        let status = response.status();
+
+        if status != StatusCode::TOO_MANY_REQUESTS {
+            return FetchError::Http {
+                url: url.to_string(),
+                status,
+            };
+        }
+
+        let retry_after = response.headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+
+        FetchError::RateLimited {
+            url: url.to_string(),
+            retry_after,
+        }
+    }
+
+    pub fn from_decode(url: &str, error: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let field = error.path().to_string();
+
+        FetchError::Decode {
+            url: url.to_string(),
+            field: (field != ".").then_some(field),
+            cause: error.into_inner().to_string(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        match self {
+            FetchError::Network { url, .. }
+            | FetchError::Timeout { url }
+            | FetchError::Http { url, .. }
+            | FetchError::RateLimited { url, .. }
+            | FetchError::Decode { url, .. } => url,
+        }
+    }
+
+    /// How long the server asked us to stay away, if it did.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            FetchError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    pub fn detail(&self) -> String {
+        match self {
+            FetchError::Network { cause, .. } => format!("{} ({}): {}", self, self.url(), cause),
+            FetchError::Decode { cause, .. } => format!("{} ({}): {}", self, self.url(), cause),
+            _ => format!("{} ({})", self, self.url()),
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Network { .. } => write!(f, "Network unavailable"),
+            FetchError::Timeout { .. } => write!(f, "Request timed out"),
+            FetchError::Http { status, .. } => match status.canonical_reason() {
+                Some(reason) => write!(f, "HTTP {} {}", status.as_u16(), reason),
+                None => write!(f, "HTTP {}", status.as_u16()),
+            },
+            FetchError::RateLimited { retry_after: Some(retry_after), .. } => {
+                write!(f, "Rate limited, retry in {} min", retry_after.as_secs().div_ceil(60))
+            }
+            FetchError::RateLimited { retry_after: None, .. } => write!(f, "Rate limited"),
+            FetchError::Decode { field: Some(field), .. } => write!(f, "Unexpected response at {}", field),
+            FetchError::Decode { field: None, .. } => write!(f, "Unexpected response"),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn parse_retry_after(raw: &str) -> Option<Duration> {
+    if let Ok(seconds) = raw.trim().parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(raw).ok()?;
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+/// reqwest keeps the useful part (DNS failure, refused connection, ...) down the source chain.
+fn describe_cause(error: &dyn Error) -> String {
+    let mut cause = error.to_string();
+    let mut source = error.source();
+
+    while let Some(inner) = source {
+        let inner_cause = inner.to_string();
+
+        // Wrappers tend to repeat the message of the error they wrap
+        if !cause.ends_with(&inner_cause) {
+            cause.push_str(": ");
+            cause.push_str(&inner_cause);
+        }
+
+        source = inner.source();
+    }
+
+    cause
+}
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use reqwest::StatusCode;
+
+use super::{get_json, FetchError, GiveawayProvider, HealthTracker, ProviderHealth};
+use crate::{games::Game, http::HttpCache, platform::Platform};
+
+const DEFAULT_BASE_URL: &str = "https://www.gamerpower.com";
+
+pub struct GamerpowerProvider {
+    base_url: String,
+    http: Arc<HttpCache>,
+    health: HealthTracker,
+}
+
+impl GamerpowerProvider {
+    pub fn new(http: Arc<HttpCache>) -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL, http)
+    }
+
+    pub fn with_base_url(base_url: &str, http: Arc<HttpCache>) -> Self {
+        GamerpowerProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+            health: HealthTracker::new(),
+        }
+    }
+
+    async fn fetch_games(&self) -> Result<Vec<Game>, FetchError> {
+        let url = format!("{}/api/giveaways?platform=pc", self.base_url);
+
+        let raw_games: Vec<Game> = match get_json(&self.http, &url).await {
+            Ok(raw_games) => raw_games,
+            // gamerpower answers 404 when there is nothing to give away
+            Err(FetchError::Http { status: StatusCode::NOT_FOUND, .. }) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self::consume_games(raw_games))
+    }
+
+    fn consume_games(raw_games: Vec<Game>) -> Vec<Game> {
+        raw_games
+            .into_iter()
+            .filter(|g| g.status.eq_ignore_ascii_case("active"))
+            .map(|mut g| {
+                // Prime Gaming and similar offers are often only named in the title
+                g.platform = Platform::from_store_list(&g.platforms)
+                    .or_else(|| Platform::classify(&g.title))
+                    .unwrap_or(Platform::Other);
+                g
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl GiveawayProvider for GamerpowerProvider {
+    fn name(&self) -> &'static str {
+        "gamerpower"
+    }
+
+    async fn fetch(&self) -> Result<Vec<Game>, FetchError> {
+        let result = self.fetch_games().await;
+        self.health.track(result).await
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        self.health.get().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    const GIVEAWAYS: &str = include_str!("../../tests/fixtures/gamerpower_giveaways.json");
+
+    async fn provider_serving(response: ResponseTemplate) -> (MockServer, GamerpowerProvider) {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/giveaways"))
+            .and(query_param("platform", "pc"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+
+        let provider = GamerpowerProvider::with_base_url(&server.uri(), Arc::new(HttpCache::new()));
+
+        (server, provider)
+    }
+
+    #[tokio::test]
+    async fn parses_active_giveaways() {
+        let (_server, provider) = provider_serving(ResponseTemplate::new(200).set_body_string(GIVEAWAYS)).await;
+
+        let games = provider.fetch().await.unwrap();
+        let titles: Vec<&str> = games.iter().map(|g| g.title.as_str()).collect();
+
+        assert_eq!(titles, ["Hollow Lantern (Epic Games) Giveaway", "Cinder Rally Key Giveaway", "SteamWorld Tinker (GOG) Giveaway"]);
+
+        let game = &games[0];
+        assert_eq!(game.id, 3101);
+        assert_eq!(game.platform, Platform::Epic);
+        assert_eq!(game.worth, Some(24.99));
+        assert_eq!(game.end_date, "2099-01-09T16:00:00Z".parse().ok());
+        assert_eq!(game.published_date, "2025-10-02T15:00:00Z".parse().ok());
+        assert_eq!(game.users, 12840);
+        assert_eq!(game.gamerpower_url.as_deref(), Some("https://www.gamerpower.com/hollow-lantern-epic-games-giveaway"));
+
+        let key = &games[1];
+        assert_eq!(key.platform, Platform::Steam);
+        assert_eq!(key.worth, None);
+        assert_eq!(key.end_date, None);
+        assert_eq!(key.instructions, None);
+
+        // "PC, DRM-Free" names no store, the title does
+        assert_eq!(games[2].platform, Platform::Gog);
+        assert!(matches!(provider.health().await, ProviderHealth::Healthy));
+    }
+
+    #[tokio::test]
+    async fn treats_not_found_as_no_giveaways() {
+        let (_server, provider) = provider_serving(ResponseTemplate::new(404).set_body_string(r#"{"status": 0}"#)).await;
+
+        assert!(provider.fetch().await.unwrap().is_empty());
+        assert!(matches!(provider.health().await, ProviderHealth::Healthy));
+    }
+
+    #[tokio::test]
+    async fn reports_server_errors() {
+        let (_server, provider) = provider_serving(ResponseTemplate::new(500)).await;
+
+        assert!(matches!(provider.fetch().await, Err(FetchError::Http { status: StatusCode::INTERNAL_SERVER_ERROR, .. })));
+        assert!(matches!(provider.health().await, ProviderHealth::Failing { .. }));
+    }
+}
@@ -0,0 +1,320 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::{get_json, FetchError, GiveawayProvider, HealthTracker, ProviderHealth};
+use crate::{games::{parse_worth, Game, GiveawayType}, http::HttpCache, platform::Platform};
+
+const DEFAULT_STORE_URL: &str = "https://www.gog.com";
+const DEFAULT_CATALOG_URL: &str = "https://catalog.gog.com";
+
+const CATALOG_QUERY: &str = "limit=48&order=desc:trending&productType=in:game,pack\
+    &countryCode=US&locale=en-US&currencyCode=USD";
+
+// Markers of the giveaway banner rendered on the store homepage
+const GIVEAWAY_LINK_MARKER: &str = "giveaway__overlay-link";
+const GAME_PATH_MARKER: &str = "/game/";
+
+#[derive(Debug, Deserialize)]
+struct CatalogResponse {
+    products: Vec<CatalogProduct>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CatalogProduct {
+    id: String,
+    slug: String,
+    title: String,
+    #[serde(default)]
+    cover_horizontal: Option<String>,
+    #[serde(default)]
+    store_link: Option<String>,
+    #[serde(default)]
+    product_type: Option<String>,
+    #[serde(default)]
+    price: Option<CatalogPrice>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CatalogPrice {
+    final_money: Money,
+    base_money: Money,
+}
+
+#[derive(Debug, Deserialize)]
+struct Money {
+    amount: String,
+}
+
+impl Money {
+    fn is_zero(&self) -> bool {
+        self.amount.parse::<f64>().map(|a| a == 0.0).unwrap_or(false)
+    }
+}
+
+impl CatalogProduct {
+    fn is_free_promotion(&self) -> bool {
+        // Titles that always cost nothing are free to play, not giveaways
+        match &self.price {
+            Some(price) => price.final_money.is_zero() && !price.base_money.is_zero(),
+            None => false,
+        }
+    }
+
+    fn into_game(self, store_url: &str, giveaway_url: Option<String>) -> Game {
+        let product_url = self.store_link
+            .unwrap_or_else(|| format!("{}/en/game/{}", store_url, self.slug));
+
+        let game_type = match self.product_type.as_deref() {
+            Some("dlc") => GiveawayType::Dlc,
+            _ => GiveawayType::Game,
+        };
+
+        Game {
+            id: Game::synthetic_id("gog", &self.id),
+            title: self.title,
+            platforms: "GOG".to_string(),
+            image: self.cover_horizontal.unwrap_or_default(),
+            platform: Platform::Gog,
+            open_giveaway_url: giveaway_url.unwrap_or(product_url),
+            game_type,
+            status: "Active".to_string(),
+            worth: self.price.and_then(|p| parse_worth(&p.base_money.amount)),
+            ..Default::default()
+        }
+    }
+}
+
+pub struct GogProvider {
+    store_url: String,
+    catalog_url: String,
+    http: Arc<HttpCache>,
+    health: HealthTracker,
+}
+
+impl GogProvider {
+    pub fn new(http: Arc<HttpCache>) -> Self {
+        Self::with_base_urls(DEFAULT_STORE_URL, DEFAULT_CATALOG_URL, http)
+    }
+
+    pub fn with_base_urls(store_url: &str, catalog_url: &str, http: Arc<HttpCache>) -> Self {
+        GogProvider {
+            store_url: store_url.trim_end_matches('/').to_string(),
+            catalog_url: catalog_url.trim_end_matches('/').to_string(),
+            http,
+            health: HealthTracker::new(),
+        }
+    }
+
+    async fn fetch_catalog(&self, filters: &str) -> Result<Vec<CatalogProduct>, FetchError> {
+        let url = format!("{}/v1/catalog?{}&{}", self.catalog_url, CATALOG_QUERY, filters);
+
+        let catalog: CatalogResponse = get_json(&self.http, &url).await?;
+
+        Ok(catalog.products)
+    }
+
+    async fn fetch_discounted(&self) -> Result<Vec<Game>, FetchError> {
+        let products = self.fetch_catalog("price=between:0,0&discounted=eq:true").await?;
+
+        Ok(products
+            .into_iter()
+            .filter(|p| p.is_free_promotion())
+            .map(|p| p.into_game(&self.store_url, None))
+            .collect())
+    }
+
+    async fn fetch_giveaway(&self) -> Result<Option<Game>, FetchError> {
+        let url = format!("{}/en/", self.store_url);
+
+        let homepage = self.http.get(&url).await?;
+
+        let Some(slug) = Self::find_giveaway_slug(&homepage) else {
+            return Ok(None);
+        };
+
+        // The banner only links the product, details come from the catalog
+        let query = format!("query=like:{}", slug.replace('_', " "));
+        let product = self.fetch_catalog(&query).await?
+            .into_iter()
+            .find(|p| p.slug == slug);
+
+        let giveaway_url = format!("{}/en/#giveaway", self.store_url);
+
+        Ok(Some(match product {
+            Some(product) => product.into_game(&self.store_url, Some(giveaway_url)),
+            None => Game {
+                id: Game::synthetic_id("gog", &slug),
+                title: Self::title_from_slug(&slug),
+                platforms: "GOG".to_string(),
+                image: String::new(),
+                platform: Platform::Gog,
+                open_giveaway_url: giveaway_url,
+                game_type: GiveawayType::Game,
+                status: "Active".to_string(),
+                ..Default::default()
+            },
+        }))
+    }
+
+    fn find_giveaway_slug(homepage: &str) -> Option<String> {
+        let banner = &homepage[homepage.find(GIVEAWAY_LINK_MARKER)?..];
+        let tag = &banner[..banner.find('>')?];
+
+        let href = &tag[tag.find("href=\"")? + "href=\"".len()..];
+        let href = &href[..href.find('"')?];
+
+        let slug = &href[href.find(GAME_PATH_MARKER)? + GAME_PATH_MARKER.len()..];
+        let slug = slug.split(['?', '#', '/']).next()?;
+
+        if slug.is_empty() {
+            None
+        } else {
+            Some(slug.to_string())
+        }
+    }
+
+    fn title_from_slug(slug: &str) -> String {
+        slug.split('_')
+            .filter(|w| !w.is_empty())
+            .map(|w| {
+                let mut chars = w.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    async fn fetch_games(&self) -> Result<Vec<Game>, FetchError> {
+        let mut games = Vec::new();
+
+        // The banner is scraped from the homepage markup, so losing it mustn't cost the catalog offers
+        match self.fetch_giveaway().await {
+            Ok(Some(giveaway)) => games.push(giveaway),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Skipping GOG giveaway banner: {}", e.detail()),
+        }
+
+        games.extend(self.fetch_discounted().await?);
+
+        Ok(games)
+    }
+}
+
+#[async_trait]
+impl GiveawayProvider for GogProvider {
+    fn name(&self) -> &'static str {
+        "gog"
+    }
+
+    async fn fetch(&self) -> Result<Vec<Game>, FetchError> {
+        let result = self.fetch_games().await;
+        self.health.track(result).await
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        self.health.get().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    const HOMEPAGE: &str = include_str!("../../tests/fixtures/gog_homepage.html");
+    const DISCOUNTED: &str = include_str!("../../tests/fixtures/gog_catalog_discounted.json");
+    const GIVEAWAY: &str = include_str!("../../tests/fixtures/gog_catalog_giveaway.json");
+
+    async fn start_server(homepage: ResponseTemplate, giveaway: &str) -> MockServer {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/en/"))
+            .respond_with(homepage)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/catalog"))
+            .and(query_param("discounted", "eq:true"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(DISCOUNTED))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/catalog"))
+            .and(query_param("query", "like:hollow lantern"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(giveaway))
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    fn provider_for(server: &MockServer) -> GogProvider {
+        GogProvider::with_base_urls(&server.uri(), &server.uri(), Arc::new(HttpCache::new()))
+    }
+
+    #[tokio::test]
+    async fn parses_giveaway_and_discounted_catalog() {
+        let server = start_server(ResponseTemplate::new(200).set_body_string(HOMEPAGE), GIVEAWAY).await;
+        let provider = provider_for(&server);
+
+        let games = provider.fetch().await.unwrap();
+        let titles: Vec<&str> = games.iter().map(|g| g.title.as_str()).collect();
+
+        assert_eq!(titles, ["Hollow Lantern", "Starfall Tactics", "Starfall Tactics Soundtrack"]);
+
+        let giveaway = &games[0];
+        assert_eq!(giveaway.id, Game::synthetic_id("gog", "1207700001"));
+        assert_eq!(giveaway.open_giveaway_url, format!("{}/en/#giveaway", server.uri()));
+        assert_eq!(giveaway.image, "https://images.gog-statics.com/hollow.jpg");
+        assert_eq!(giveaway.worth, Some(19.99));
+
+        let discounted = &games[1];
+        assert_eq!(discounted.platform, Platform::Gog);
+        assert_eq!(discounted.open_giveaway_url, "https://www.gog.com/en/game/starfall_tactics");
+        assert_eq!(discounted.worth, Some(14.99));
+
+        let dlc = &games[2];
+        assert_eq!(dlc.game_type, GiveawayType::Dlc);
+        assert_eq!(dlc.open_giveaway_url, format!("{}/en/game/starfall_tactics_soundtrack", server.uri()));
+    }
+
+    #[tokio::test]
+    async fn names_giveaway_after_slug_when_catalog_misses_it() {
+        let server = start_server(ResponseTemplate::new(200).set_body_string(HOMEPAGE), r#"{"products": []}"#).await;
+
+        let games = provider_for(&server).fetch().await.unwrap();
+
+        assert_eq!(games[0].title, "Hollow Lantern");
+        assert_eq!(games[0].id, Game::synthetic_id("gog", "hollow_lantern"));
+        assert_eq!(games[0].worth, None);
+    }
+
+    #[tokio::test]
+    async fn keeps_catalog_offers_when_homepage_fails() {
+        let server = start_server(ResponseTemplate::new(503), GIVEAWAY).await;
+        let provider = provider_for(&server);
+
+        let games = provider.fetch().await.unwrap();
+
+        assert_eq!(games.len(), 2);
+        assert!(matches!(provider.health().await, ProviderHealth::Healthy));
+    }
+
+    #[test]
+    fn finds_no_slug_without_banner() {
+        assert_eq!(GogProvider::find_giveaway_slug(r#"<a href="/en/game/other_game">"#), None);
+    }
+}
@@ -0,0 +1,109 @@
+mod epic;
+mod error;
+mod gamerpower;
+mod gog;
+mod steam;
+
+use std::sync::Arc;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use tokio::sync::Mutex;
+
+use crate::{config::ProvidersConfig, games::Game, http::HttpCache};
+
+pub use epic::EpicProvider;
+pub use error::FetchError;
+pub use gamerpower::GamerpowerProvider;
+pub use gog::GogProvider;
+pub use steam::SteamProvider;
+
+#[derive(Debug, Clone)]
+pub enum ProviderHealth {
+    Unknown,
+    Healthy,
+    Failing {
+        error: FetchError,
+        since: DateTime<Utc>,
+    },
+}
+
+#[async_trait]
+pub trait GiveawayProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn fetch(&self) -> Result<Vec<Game>, FetchError>;
+
+    async fn health(&self) -> ProviderHealth;
+}
+
+pub struct HealthTracker {
+    state: Mutex<ProviderHealth>,
+}
+
+impl HealthTracker {
+    pub fn new() -> Self {
+        HealthTracker {
+            state: Mutex::new(ProviderHealth::Unknown),
+        }
+    }
+
+    pub async fn get(&self) -> ProviderHealth {
+        self.state.lock().await.clone()
+    }
+
+    pub async fn track<T>(&self, result: Result<T, FetchError>) -> Result<T, FetchError> {
+        let mut state = self.state.lock().await;
+
+        match &result {
+            Ok(_) => {
+                *state = ProviderHealth::Healthy;
+            }
+            Err(e) => {
+                // Keep the original failure time while the provider keeps failing
+                let since = match &*state {
+                    ProviderHealth::Failing { since, .. } => *since,
+                    _ => Utc::now(),
+                };
+
+                *state = ProviderHealth::Failing {
+                    error: e.clone(),
+                    since,
+                };
+            }
+        }
+
+        result
+    }
+}
+
+pub fn enabled_providers(config: &ProvidersConfig, http: &Arc<HttpCache>) -> Vec<Box<dyn GiveawayProvider>> {
+    let mut providers: Vec<Box<dyn GiveawayProvider>> = Vec::new();
+
+    // Direct store providers go first so their entries win over gamerpower duplicates
+    if config.epic {
+        providers.push(Box::new(EpicProvider::new(http.clone())));
+    }
+
+    if config.gog {
+        providers.push(Box::new(GogProvider::new(http.clone())));
+    }
+
+    if config.steam {
+        providers.push(Box::new(SteamProvider::new(http.clone())));
+    }
+
+    if config.gamerpower {
+        providers.push(Box::new(GamerpowerProvider::new(http.clone())));
+    }
+
+    providers
+}
+
+/// Parses the body keeping track of where it stopped matching, so the error names the field.
+pub(crate) async fn get_json<T: DeserializeOwned>(http: &HttpCache, url: &str) -> Result<T, FetchError> {
+    let body = http.get(url).await?;
+    let deserializer = &mut serde_json::Deserializer::from_str(&body);
+
+    serde_path_to_error::deserialize(deserializer).map_err(|e| FetchError::from_decode(url, e))
+}
@@ -0,0 +1,509 @@
+use std::{collections::{HashMap, HashSet}, sync::Arc};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use super::{get_json, FetchError, GiveawayProvider, HealthTracker, ProviderHealth};
+use crate::{games::{Game, GiveawayType}, http::HttpCache, platform::Platform};
+
+const DEFAULT_BASE_URL: &str = "https://store.steampowered.com";
+const DEFAULT_API_URL: &str = "https://api.steampowered.com";
+
+const SEARCH_QUERY: &str = "specials=1&maxprice=free&category1=998&count=25&json=1&cc=us&l=en";
+
+/// appdetails allows about 200 requests per 5 minutes, so candidates are checked a few at a time.
+const APPDETAILS_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Deserialize)]
+struct FeaturedCategories {
+    specials: FeaturedSpecials,
+}
+
+#[derive(Debug, Deserialize)]
+struct FeaturedSpecials {
+    #[serde(default)]
+    items: Vec<FeaturedItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FeaturedItem {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    discount_percent: u32,
+    #[serde(default)]
+    original_price: Option<u64>,
+    #[serde(default)]
+    final_price: u64,
+    #[serde(default)]
+    header_image: Option<String>,
+    #[serde(default)]
+    discount_expiration: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResults {
+    #[serde(default)]
+    items: Vec<SearchItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchItem {
+    logo: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppDetailsEntry {
+    success: bool,
+    #[serde(default)]
+    data: Option<AppDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppDetails {
+    #[serde(rename = "type", default)]
+    app_type: String,
+    name: String,
+    #[serde(default)]
+    is_free: bool,
+    #[serde(default)]
+    header_image: Option<String>,
+    #[serde(default)]
+    price_overview: Option<PriceOverview>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PriceOverview {
+    initial: u64,
+    #[serde(rename = "final")]
+    final_price: u64,
+    discount_percent: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct StoreItemsRequest {
+    ids: Vec<StoreItemId>,
+    context: StoreContext,
+    data_request: StoreDataRequest,
+}
+
+#[derive(Debug, Serialize)]
+struct StoreItemId {
+    appid: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct StoreContext {
+    language: &'static str,
+    country_code: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct StoreDataRequest {
+    include_all_purchase_options: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct StoreItemsResponse {
+    response: StoreItems,
+}
+
+#[derive(Debug, Deserialize)]
+struct StoreItems {
+    #[serde(default)]
+    store_items: Vec<StoreItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StoreItem {
+    #[serde(default)]
+    appid: Option<u64>,
+    #[serde(default)]
+    best_purchase_option: Option<PurchaseOption>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PurchaseOption {
+    #[serde(default)]
+    active_discounts: Vec<ActiveDiscount>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActiveDiscount {
+    #[serde(default)]
+    discount_end_date: Option<i64>,
+}
+
+/// A store item that costs nothing right now but normally has a price,
+/// as opposed to apps that are free to play all the time.
+struct FreePromotion {
+    app_id: u64,
+    name: String,
+    header_image: Option<String>,
+    game_type: GiveawayType,
+    worth: Option<f64>,
+    ends_at: Option<DateTime<Utc>>,
+}
+
+impl FeaturedItem {
+    fn into_promotion(self) -> Option<FreePromotion> {
+        let original_price = self.original_price.unwrap_or(0);
+
+        if self.discount_percent < 100 || self.final_price > 0 || original_price == 0 {
+            return None;
+        }
+
+        Some(FreePromotion {
+            app_id: self.id,
+            name: self.name,
+            header_image: self.header_image,
+            game_type: GiveawayType::Game,
+            worth: Some(original_price as f64 / 100.0),
+            ends_at: self.discount_expiration.and_then(|ts| DateTime::from_timestamp(ts, 0)),
+        })
+    }
+}
+
+impl AppDetails {
+    fn into_promotion(self, app_id: u64) -> Option<FreePromotion> {
+        let price = self.price_overview?;
+
+        if self.is_free || price.discount_percent < 100 || price.final_price > 0 || price.initial == 0 {
+            return None;
+        }
+
+        Some(FreePromotion {
+            app_id,
+            name: self.name,
+            header_image: self.header_image,
+            game_type: match self.app_type.as_str() {
+                "dlc" => GiveawayType::Dlc,
+                _ => GiveawayType::Game,
+            },
+            worth: Some(price.initial as f64 / 100.0),
+            // appdetails has no discount end, it's looked up separately
+            ends_at: None,
+        })
+    }
+}
+
+impl FreePromotion {
+    fn into_game(self, base_url: &str) -> Game {
+        let image = self.header_image.unwrap_or_else(|| format!(
+            "https://cdn.akamai.steamstatic.com/steam/apps/{}/header.jpg",
+            self.app_id,
+        ));
+
+        Game {
+            id: Game::synthetic_id("steam", &self.app_id.to_string()),
+            title: self.name,
+            platforms: "Steam".to_string(),
+            image,
+            platform: Platform::Steam,
+            open_giveaway_url: format!("{}/app/{}/", base_url, self.app_id),
+            game_type: self.game_type,
+            status: "Active".to_string(),
+            worth: self.worth,
+            end_date: self.ends_at,
+            ..Default::default()
+        }
+    }
+}
+
+pub struct SteamProvider {
+    base_url: String,
+    api_url: String,
+    http: Arc<HttpCache>,
+    health: HealthTracker,
+}
+
+impl SteamProvider {
+    pub fn new(http: Arc<HttpCache>) -> Self {
+        Self::with_base_urls(DEFAULT_BASE_URL, DEFAULT_API_URL, http)
+    }
+
+    pub fn with_base_urls(base_url: &str, api_url: &str, http: Arc<HttpCache>) -> Self {
+        SteamProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            http,
+            health: HealthTracker::new(),
+        }
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(&self, path: &str) -> Result<T, FetchError> {
+        let url = format!("{}{}", self.base_url, path);
+
+        get_json(&self.http, &url).await
+    }
+
+    async fn fetch_featured(&self) -> Result<Vec<FreePromotion>, FetchError> {
+        let featured: FeaturedCategories = self.get_json("/api/featuredcategories?cc=us&l=en").await?;
+
+        Ok(featured.specials.items
+            .into_iter()
+            .filter_map(FeaturedItem::into_promotion)
+            .collect())
+    }
+
+    async fn fetch_searched(&self, known: &HashSet<u64>) -> Result<Vec<FreePromotion>, FetchError> {
+        let path = format!("/search/results/?{}", SEARCH_QUERY);
+        let results: SearchResults = self.get_json(&path).await?;
+
+        let app_ids: Vec<u64> = results.items
+            .iter()
+            .filter_map(|item| Self::app_id_from_logo(&item.logo))
+            .filter(|id| !known.contains(id))
+            .collect();
+
+        let mut promotions = Vec::new();
+
+        // Search results carry no prices, each candidate has to be checked
+        for chunk in app_ids.chunks(APPDETAILS_CONCURRENCY) {
+            let results = join_all(chunk.iter().map(|app_id| self.fetch_app_details(*app_id))).await;
+            let mut is_rate_limited = false;
+
+            for (app_id, result) in chunk.iter().zip(results) {
+                match result {
+                    Ok(promotion) => promotions.extend(promotion),
+                    Err(e) => {
+                        is_rate_limited |= matches!(e, FetchError::RateLimited { .. });
+                        tracing::warn!("Skipping Steam app {}: {}", app_id, e.detail());
+                    }
+                }
+            }
+
+            if is_rate_limited {
+                tracing::warn!("Steam rate limited appdetails, the remaining candidates wait for the next refresh");
+                break;
+            }
+        }
+
+        Ok(promotions)
+    }
+
+    async fn fetch_app_details(&self, app_id: u64) -> Result<Option<FreePromotion>, FetchError> {
+        let path = format!("/api/appdetails?appids={}&cc=us&l=en", app_id);
+        let mut details: HashMap<String, AppDetailsEntry> = self.get_json(&path).await?;
+
+        Ok(details.remove(&app_id.to_string())
+            .filter(|entry| entry.success)
+            .and_then(|entry| entry.data)
+            .and_then(|data| data.into_promotion(app_id)))
+    }
+
+    /// End dates of the discounts, keyed by app id, for promotions found without one.
+    async fn fetch_discount_ends(&self, app_ids: &[u64]) -> Result<HashMap<u64, DateTime<Utc>>, FetchError> {
+        let request = StoreItemsRequest {
+            ids: app_ids.iter().map(|&appid| StoreItemId { appid }).collect(),
+            context: StoreContext {
+                language: "english",
+                country_code: "US",
+            },
+            data_request: StoreDataRequest {
+                include_all_purchase_options: true,
+            },
+        };
+
+        let input = serde_json::to_string(&request).expect("Store items request is always serializable");
+        let url = Url::parse_with_params(
+            &format!("{}/IStoreBrowseService/GetItems/v1", self.api_url),
+            [("input_json", input)],
+        ).map_err(|e| FetchError::Network {
+            url: self.api_url.clone(),
+            cause: e.to_string(),
+        })?;
+
+        let items: StoreItemsResponse = get_json(&self.http, url.as_str()).await?;
+
+        Ok(items.response.store_items
+            .into_iter()
+            .filter_map(|item| {
+                let ends_at = item.best_purchase_option?
+                    .active_discounts
+                    .iter()
+                    .filter_map(|discount| discount.discount_end_date)
+                    .max()
+                    .and_then(|ts| DateTime::from_timestamp(ts, 0))?;
+
+                Some((item.appid?, ends_at))
+            })
+            .collect())
+    }
+
+    fn app_id_from_logo(logo: &str) -> Option<u64> {
+        let rest = &logo[logo.find("/apps/")? + "/apps/".len()..];
+        rest.split('/').next()?.parse().ok()
+    }
+
+    async fn fetch_games(&self) -> Result<Vec<Game>, FetchError> {
+        let mut promotions = self.fetch_featured().await?;
+
+        let known: HashSet<u64> = promotions.iter().map(|p| p.app_id).collect();
+
+        match self.fetch_searched(&known).await {
+            Ok(searched) => promotions.extend(searched),
+            Err(e) => tracing::warn!("Skipping Steam search results: {}", e.detail()),
+        }
+
+        let undated: Vec<u64> = promotions.iter()
+            .filter(|p| p.ends_at.is_none())
+            .map(|p| p.app_id)
+            .collect();
+
+        if !undated.is_empty() {
+            match self.fetch_discount_ends(&undated).await {
+                Ok(ends) => {
+                    for promotion in &mut promotions {
+                        promotion.ends_at = promotion.ends_at.or(ends.get(&promotion.app_id).copied());
+                    }
+                }
+                Err(e) => tracing::warn!("Skipping Steam discount end dates: {}", e.detail()),
+            }
+        }
+
+        Ok(promotions
+            .into_iter()
+            .map(|p| p.into_game(&self.base_url))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl GiveawayProvider for SteamProvider {
+    fn name(&self) -> &'static str {
+        "steam"
+    }
+
+    async fn fetch(&self) -> Result<Vec<Game>, FetchError> {
+        let result = self.fetch_games().await;
+        self.health.track(result).await
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        self.health.get().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    const FEATURED: &str = include_str!("../../tests/fixtures/steam_featured.json");
+    const SEARCH: &str = include_str!("../../tests/fixtures/steam_search.json");
+    const APPDETAILS_DLC: &str = include_str!("../../tests/fixtures/steam_appdetails_200.json");
+    const APPDETAILS_FREE_TO_PLAY: &str = include_str!("../../tests/fixtures/steam_appdetails_300.json");
+    const STORE_ITEMS: &str = include_str!("../../tests/fixtures/steam_store_items.json");
+
+    async fn start_server(search: ResponseTemplate) -> MockServer {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/featuredcategories"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FEATURED))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/search/results/"))
+            .and(query_param("maxprice", "free"))
+            .respond_with(search)
+            .mount(&server)
+            .await;
+
+        for (app_id, response) in [
+            ("200", ResponseTemplate::new(200).set_body_string(APPDETAILS_DLC)),
+            ("300", ResponseTemplate::new(200).set_body_string(APPDETAILS_FREE_TO_PLAY)),
+            ("400", ResponseTemplate::new(500)),
+        ] {
+            Mock::given(method("GET"))
+                .and(path("/api/appdetails"))
+                .and(query_param("appids", app_id))
+                .respond_with(response)
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        Mock::given(method("GET"))
+            .and(path("/IStoreBrowseService/GetItems/v1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(STORE_ITEMS))
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    fn provider_for(server: &MockServer) -> SteamProvider {
+        SteamProvider::with_base_urls(&server.uri(), &server.uri(), Arc::new(HttpCache::new()))
+    }
+
+    #[tokio::test]
+    async fn combines_featured_and_searched_promotions() {
+        let server = start_server(ResponseTemplate::new(200).set_body_string(SEARCH)).await;
+
+        let games = provider_for(&server).fetch().await.unwrap();
+        let titles: Vec<&str> = games.iter().map(|g| g.title.as_str()).collect();
+
+        assert_eq!(titles, ["Orbital Gardener", "Orbital Gardener - Seed Pack"]);
+
+        let featured = &games[0];
+        assert_eq!(featured.id, Game::synthetic_id("steam", "100"));
+        assert_eq!(featured.platform, Platform::Steam);
+        assert_eq!(featured.game_type, GiveawayType::Game);
+        assert_eq!(featured.open_giveaway_url, format!("{}/app/100/", server.uri()));
+        assert_eq!(featured.worth, Some(19.99));
+        assert_eq!(featured.end_date, DateTime::from_timestamp(4070908800, 0));
+
+        let dlc = &games[1];
+        assert_eq!(dlc.game_type, GiveawayType::Dlc);
+        assert_eq!(dlc.image, "https://shared.steamstatic.example/apps/200/header.jpg");
+        assert_eq!(dlc.worth, Some(4.99));
+        assert_eq!(dlc.end_date, DateTime::from_timestamp(4070822400, 0));
+    }
+
+    #[tokio::test]
+    async fn keeps_featured_promotions_when_search_fails() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/featuredcategories"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(FEATURED))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/search/results/"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let games = provider_for(&server).fetch().await.unwrap();
+
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].title, "Orbital Gardener");
+    }
+
+    #[tokio::test]
+    async fn fails_without_featured_categories() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/featuredcategories"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "30"))
+            .mount(&server)
+            .await;
+
+        let provider = provider_for(&server);
+
+        assert!(matches!(provider.fetch().await, Err(FetchError::RateLimited { .. })));
+        assert!(matches!(provider.health().await, ProviderHealth::Failing { .. }));
+    }
+}
@@ -1,11 +1,20 @@
 use std::ops::Deref;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResourceIcon {
     Main,
 
     BrandEpic,
     BrandSteam,
     BrandGog,
+    BrandItch,
+    BrandUbisoft,
+    BrandEa,
+    BrandBattlenet,
+    BrandPrime,
+    BrandHumble,
+    BrandIndiegala,
+    BrandGeneric,
     BrandGithub,
 
     NotificationsEnabled,
@@ -22,17 +31,48 @@ impl ResourceIcon {
             ResourceIcon::BrandGithub => "tray-brand-github-icon",
             ResourceIcon::BrandGog => "tray-brand-gog-icon",
             ResourceIcon::BrandSteam => "tray-brand-steam-icon",
+            ResourceIcon::BrandItch => "tray-brand-itch-icon",
+            ResourceIcon::BrandUbisoft => "tray-brand-ubisoft-icon",
+            ResourceIcon::BrandEa => "tray-brand-ea-icon",
+            ResourceIcon::BrandBattlenet => "tray-brand-battlenet-icon",
+            ResourceIcon::BrandPrime => "tray-brand-prime-icon",
+            ResourceIcon::BrandHumble => "tray-brand-humble-icon",
+            ResourceIcon::BrandIndiegala => "tray-brand-indiegala-icon",
+            ResourceIcon::BrandGeneric => "tray-brand-generic-icon",
             ResourceIcon::NotificationsDisabled => "tray-notifications-disabled-icon",
             ResourceIcon::NotificationsEnabled => "tray-notifications-enabled-icon",
             ResourceIcon::Refresh => "tray-refresh-icon",
         }
     }
+
+    /// Raw `.ico` file, for platforms without Windows resources. Mirrors `icons.rc`.
+    #[cfg(target_os = "linux")]
+    pub fn get_icon_bytes(&self) -> &'static [u8] {
+        match self {
+            ResourceIcon::Main => include_bytes!("../assets/main.ico"),
+            ResourceIcon::BrandEpic => include_bytes!("../assets/epic.ico"),
+            ResourceIcon::BrandGithub => include_bytes!("../assets/github.ico"),
+            ResourceIcon::BrandGog => include_bytes!("../assets/gog.ico"),
+            ResourceIcon::BrandSteam => include_bytes!("../assets/steam.ico"),
+            ResourceIcon::BrandItch => include_bytes!("../assets/itch.ico"),
+            ResourceIcon::BrandUbisoft => include_bytes!("../assets/ubisoft.ico"),
+            ResourceIcon::BrandEa => include_bytes!("../assets/ea.ico"),
+            ResourceIcon::BrandBattlenet => include_bytes!("../assets/battlenet.ico"),
+            ResourceIcon::BrandPrime => include_bytes!("../assets/prime.ico"),
+            ResourceIcon::BrandHumble => include_bytes!("../assets/humble.ico"),
+            ResourceIcon::BrandIndiegala => include_bytes!("../assets/indiegala.ico"),
+            ResourceIcon::BrandGeneric => include_bytes!("../assets/store.ico"),
+            ResourceIcon::NotificationsDisabled => include_bytes!("../assets/notifications-disabled.ico"),
+            ResourceIcon::NotificationsEnabled => include_bytes!("../assets/notifications-enabled.ico"),
+            ResourceIcon::Refresh => include_bytes!("../assets/refresh.ico"),
+        }
+    }
 }
 
 impl Deref for ResourceIcon {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
-        &self.get_icon_path()
+        self.get_icon_path()
     }
 }
\ No newline at end of file
@@ -0,0 +1,226 @@
+use regex::{Regex, RegexBuilder};
+
+use crate::config::{ExcludedAction, RulesConfig};
+use crate::games::Game;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Show,
+    Silence,
+    Hide,
+}
+
+pub struct RuleSet {
+    config: RulesConfig,
+    include_titles: Vec<Regex>,
+    exclude_titles: Vec<Regex>,
+}
+
+impl RuleSet {
+    pub fn new(config: RulesConfig) -> Self {
+        let include_titles = Self::compile_patterns(&config.include_title_patterns);
+        let exclude_titles = Self::compile_patterns(&config.exclude_title_patterns);
+
+        RuleSet {
+            config,
+            include_titles,
+            exclude_titles,
+        }
+    }
+
+    fn compile_patterns(patterns: &[String]) -> Vec<Regex> {
+        patterns
+            .iter()
+            .filter_map(|pattern| {
+                match RegexBuilder::new(pattern).case_insensitive(true).build() {
+                    Ok(regex) => Some(regex),
+                    Err(e) => {
+                        // A typo in one rule should not take every other rule down with it
+                        tracing::error!("Ignoring invalid title pattern {:?}: {}", pattern, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    pub fn evaluate(&self, game: &Game) -> Verdict {
+        if self.is_included(game) && !self.is_excluded(game) {
+            return Verdict::Show;
+        }
+
+        match self.config.excluded_action {
+            ExcludedAction::Hide => Verdict::Hide,
+            ExcludedAction::Silence => Verdict::Silence,
+        }
+    }
+
+    fn is_included(&self, game: &Game) -> bool {
+        let config = &self.config;
+
+        let platform_ok = config.include_platforms.is_empty()
+            || config.include_platforms.contains(&game.platform);
+        let type_ok = config.include_types.is_empty()
+            || config.include_types.contains(&game.game_type);
+        let title_ok = self.include_titles.is_empty()
+            || self.include_titles.iter().any(|r| r.is_match(&game.title));
+        let keyword_ok = config.include_keywords.is_empty()
+            || config.include_keywords.iter().any(|k| Self::mentions(game, k));
+
+        // Offers without a known worth are not punished for it
+        let worth_ok = match (config.min_worth, game.worth) {
+            (Some(min_worth), Some(worth)) => worth >= min_worth,
+            _ => true,
+        };
+
+        platform_ok && type_ok && title_ok && keyword_ok && worth_ok
+    }
+
+    fn is_excluded(&self, game: &Game) -> bool {
+        let config = &self.config;
+
+        config.exclude_platforms.contains(&game.platform)
+            || config.exclude_types.contains(&game.game_type)
+            || self.exclude_titles.iter().any(|r| r.is_match(&game.title))
+            || config.exclude_keywords.iter().any(|k| Self::mentions(game, k))
+    }
+
+    fn mentions(game: &Game, keyword: &str) -> bool {
+        let keyword = keyword.to_lowercase();
+
+        [Some(&game.title), game.description.as_ref(), game.instructions.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains(&keyword))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{games::GiveawayType, platform::Platform};
+
+    fn game() -> Game {
+        Game {
+            title: "Hollow Lantern".to_string(),
+            platform: Platform::Epic,
+            game_type: GiveawayType::Game,
+            worth: Some(19.99),
+            description: Some("A roguelike lit by a single lantern.".to_string()),
+            instructions: Some("Claim it on the Epic Games Store.".to_string()),
+            ..Game::default()
+        }
+    }
+
+    #[test]
+    fn evaluates_rules() {
+        let cases: [(&str, RulesConfig, Game, Verdict); 14] = [
+            ("no rules", RulesConfig::default(), game(), Verdict::Show),
+            (
+                "included platform",
+                RulesConfig { include_platforms: vec![Platform::Epic, Platform::Gog], ..Default::default() },
+                game(),
+                Verdict::Show,
+            ),
+            (
+                "platform not included",
+                RulesConfig { include_platforms: vec![Platform::Steam], ..Default::default() },
+                game(),
+                Verdict::Hide,
+            ),
+            (
+                "exclusion wins over inclusion",
+                RulesConfig {
+                    include_platforms: vec![Platform::Epic],
+                    exclude_title_patterns: vec!["^hollow".to_string()],
+                    ..Default::default()
+                },
+                game(),
+                Verdict::Hide,
+            ),
+            (
+                "type not included",
+                RulesConfig { include_types: vec![GiveawayType::Dlc], ..Default::default() },
+                game(),
+                Verdict::Hide,
+            ),
+            (
+                "excluded type",
+                RulesConfig { exclude_types: vec![GiveawayType::Game], ..Default::default() },
+                game(),
+                Verdict::Hide,
+            ),
+            (
+                "title pattern ignores case",
+                RulesConfig { include_title_patterns: vec!["LANTERN$".to_string()], ..Default::default() },
+                game(),
+                Verdict::Show,
+            ),
+            (
+                "invalid pattern is skipped",
+                RulesConfig { exclude_title_patterns: vec!["(".to_string()], ..Default::default() },
+                game(),
+                Verdict::Show,
+            ),
+            (
+                "keyword in description",
+                RulesConfig { include_keywords: vec!["Roguelike".to_string()], ..Default::default() },
+                game(),
+                Verdict::Show,
+            ),
+            (
+                "keyword in instructions",
+                RulesConfig { exclude_keywords: vec!["epic games store".to_string()], ..Default::default() },
+                game(),
+                Verdict::Hide,
+            ),
+            (
+                "keyword nowhere",
+                RulesConfig { include_keywords: vec!["racing".to_string()], ..Default::default() },
+                game(),
+                Verdict::Hide,
+            ),
+            (
+                "worth below minimum",
+                RulesConfig { min_worth: Some(20.0), ..Default::default() },
+                game(),
+                Verdict::Hide,
+            ),
+            (
+                "worth at minimum",
+                RulesConfig { min_worth: Some(19.99), ..Default::default() },
+                game(),
+                Verdict::Show,
+            ),
+            (
+                "unknown worth passes minimum",
+                RulesConfig { min_worth: Some(20.0), ..Default::default() },
+                Game { worth: None, ..game() },
+                Verdict::Show,
+            ),
+        ];
+
+        for (name, config, game, expected) in cases {
+            assert_eq!(RuleSet::new(config).evaluate(&game), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn silences_instead_of_hiding_when_asked() {
+        let cases = [
+            (ExcludedAction::Hide, Verdict::Hide),
+            (ExcludedAction::Silence, Verdict::Silence),
+        ];
+
+        for (excluded_action, expected) in cases {
+            let rules = RuleSet::new(RulesConfig {
+                excluded_action,
+                exclude_platforms: vec![Platform::Epic],
+                ..Default::default()
+            });
+
+            assert_eq!(rules.evaluate(&game()), expected, "{:?}", excluded_action);
+            assert_eq!(rules.evaluate(&Game { platform: Platform::Gog, ..game() }), Verdict::Show);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use croner::Cron;
+use fastrand::Rng;
+
+use crate::{config::RefreshConfig, games::Games};
+
+/// Longest backoff exponent, keeps the retry delay from overflowing after a long outage.
+const MAX_BACKOFF_SHIFT: u32 = 10;
+
+/// Decides how long to wait before the next automatic refresh.
+pub struct RefreshSchedule {
+    config: RefreshConfig,
+    cron: Option<Cron>,
+    failures: u32,
+    /// Source of the jitter.
+    rng: Rng,
+}
+
+impl RefreshSchedule {
+    pub fn new(config: RefreshConfig) -> Result<Self> {
+        Self::with_rng(config, Rng::new())
+    }
+
+    pub fn with_rng(config: RefreshConfig, rng: Rng) -> Result<Self> {
+        let cron = match &config.cron {
+            Some(pattern) => Some(
+                Cron::new(pattern)
+                    .parse()
+                    .with_context(|| format!("Invalid refresh cron expression {}", pattern))?,
+            ),
+            None => None,
+        };
+
+        Ok(RefreshSchedule {
+            config,
+            cron,
+            failures: 0,
+            rng,
+        })
+    }
+
+    /// Delay after the refresh that just happened, shorter when any provider failed.
+    pub async fn next_delay(&mut self, games: &Games) -> Duration {
+        let errors = games.get_error_reasons().await;
+        let delay = self.get_delay(!errors.is_empty(), Utc::now());
+
+        // A rate-limited provider asked for a break, retrying sooner would only extend it
+        let retry_after = errors.iter()
+            .filter_map(|e| e.error.retry_after())
+            .max()
+            .unwrap_or_default();
+        let delay = delay.max(retry_after);
+
+        tracing::info!("Next refresh in {} minutes", delay.as_secs() / 60);
+
+        delay
+    }
+
+    fn get_delay(&mut self, has_errors: bool, now: DateTime<Utc>) -> Duration {
+        let regular = self.get_regular_delay(now);
+
+        let delay = if has_errors {
+            let shift = self.failures.min(MAX_BACKOFF_SHIFT);
+            self.failures += 1;
+
+            let retry = Duration::from_secs(u64::from(self.config.retry_minutes) * 60) * (1 << shift);
+            retry.min(regular)
+        } else {
+            self.failures = 0;
+            regular
+        };
+
+        // Jitter never more than doubles a short retry
+        let jitter = Duration::from_secs(u64::from(self.config.jitter_minutes) * 60).min(delay);
+        let jitter = Duration::from_secs(self.rng.u64(0..=jitter.as_secs()));
+
+        delay + jitter
+    }
+
+    fn get_regular_delay(&self, now: DateTime<Utc>) -> Duration {
+        let next_occurrence = self.cron.as_ref()
+            .and_then(|cron| cron.find_next_occurrence(&now, false).ok());
+
+        match next_occurrence {
+            Some(next) => (next - now).to_std().unwrap_or_default(),
+            // Anything under a minute would just hammer the providers
+            None => Duration::from_secs(u64::from(self.config.interval_minutes.max(1)) * 60),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn now() -> DateTime<Utc> {
+        // A Wednesday
+        "2025-10-01T12:00:00Z".parse().unwrap()
+    }
+
+    fn schedule(config: RefreshConfig) -> RefreshSchedule {
+        RefreshSchedule::with_rng(config, Rng::with_seed(7)).unwrap()
+    }
+
+    fn without_jitter() -> RefreshConfig {
+        RefreshConfig {
+            jitter_minutes: 0,
+            ..RefreshConfig::default()
+        }
+    }
+
+    #[test]
+    fn waits_the_interval_after_a_good_refresh() {
+        let mut schedule = schedule(without_jitter());
+
+        assert_eq!(schedule.get_delay(false, now()), 180 * MINUTE);
+    }
+
+    #[test]
+    fn doubles_the_retry_delay_up_to_the_interval() {
+        let mut schedule = schedule(without_jitter());
+
+        let delays: Vec<u64> = (0..7)
+            .map(|_| schedule.get_delay(true, now()).as_secs() / 60)
+            .collect();
+
+        assert_eq!(delays, [5, 10, 20, 40, 80, 160, 180]);
+
+        // One good refresh starts the backoff over
+        assert_eq!(schedule.get_delay(false, now()), 180 * MINUTE);
+        assert_eq!(schedule.get_delay(true, now()), 5 * MINUTE);
+    }
+
+    #[test]
+    fn stops_doubling_after_the_longest_backoff() {
+        let mut schedule = schedule(RefreshConfig {
+            interval_minutes: u32::MAX,
+            retry_minutes: 1,
+            ..without_jitter()
+        });
+
+        let delays: Vec<u64> = (0..MAX_BACKOFF_SHIFT + 3)
+            .map(|_| schedule.get_delay(true, now()).as_secs() / 60)
+            .collect();
+
+        assert_eq!(delays[MAX_BACKOFF_SHIFT as usize], 1 << MAX_BACKOFF_SHIFT);
+        assert_eq!(delays.last(), Some(&(1 << MAX_BACKOFF_SHIFT)));
+    }
+
+    #[test]
+    fn adds_jitter_within_bounds() {
+        let mut schedule = schedule(RefreshConfig::default());
+
+        for _ in 0..100 {
+            let delay = schedule.get_delay(false, now());
+            assert!((180 * MINUTE..=190 * MINUTE).contains(&delay), "{:?}", delay);
+        }
+
+        // A 5 minute retry gets at most 5 minutes on top, not the full 10
+        for seed in 0..100 {
+            let mut schedule = RefreshSchedule::with_rng(RefreshConfig::default(), Rng::with_seed(seed)).unwrap();
+            let delay = schedule.get_delay(true, now());
+            assert!((5 * MINUTE..=10 * MINUTE).contains(&delay), "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn draws_the_same_jitter_from_the_same_seed() {
+        let delays: Vec<Duration> = (0..2)
+            .map(|_| schedule(RefreshConfig::default()).get_delay(false, now()))
+            .collect();
+
+        assert_eq!(delays[0], delays[1]);
+    }
+
+    #[test]
+    fn waits_for_the_next_cron_occurrence() {
+        let mut schedule = schedule(RefreshConfig {
+            cron: Some("5 17 * * THU".to_string()),
+            ..without_jitter()
+        });
+
+        // Thursday 17:05
+        assert_eq!(schedule.get_delay(false, now()), (29 * 60 + 5) * MINUTE);
+        assert_eq!(schedule.get_delay(true, now()), 5 * MINUTE);
+    }
+
+    #[test]
+    fn rejects_invalid_cron_expressions() {
+        assert!(RefreshSchedule::new(RefreshConfig {
+            cron: Some("every thursday".to_string()),
+            ..RefreshConfig::default()
+        }).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Response;
+use serde::Serialize;
+use tokio::{sync::Mutex, time::{sleep_until, Instant}};
+
+use super::{request_with_retries, Notification, NotificationSink, MAX_RETRY_WAIT};
+use crate::http;
+
+/// Discord's blurple, used as the embed accent.
+const EMBED_COLOR: u32 = 0x5865F2;
+
+#[derive(Serialize)]
+struct WebhookMessage<'a> {
+    username: &'a str,
+    embeds: [Embed<'a>; 1],
+}
+
+#[derive(Serialize)]
+struct Embed<'a> {
+    title: &'a str,
+    url: &'a str,
+    description: String,
+    color: u32,
+    fields: Vec<EmbedField>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<EmbedImage<'a>>,
+}
+
+#[derive(Serialize)]
+struct EmbedField {
+    name: &'static str,
+    value: String,
+    inline: bool,
+}
+
+#[derive(Serialize)]
+struct EmbedImage<'a> {
+    url: &'a str,
+}
+
+/// Posts every notification as a rich embed to a Discord channel webhook.
+pub struct DiscordSink {
+    webhook_url: String,
+    username: String,
+    /// Set when Discord reports the bucket as empty, the next message waits for it.
+    blocked_until: Mutex<Option<Instant>>,
+}
+
+impl DiscordSink {
+    pub fn new(webhook_url: &str, username: &str) -> Self {
+        DiscordSink {
+            webhook_url: webhook_url.to_string(),
+            username: username.to_string(),
+            blocked_until: Mutex::new(None),
+        }
+    }
+
+    fn build_message<'a>(&'a self, notification: &'a Notification) -> WebhookMessage<'a> {
+        let game = &notification.game;
+        let url = game.get_open_url();
+
+        let mut fields = vec![EmbedField {
+            name: "Platform",
+            value: game.platform.label().to_string(),
+            inline: true,
+        }];
+
+        if let Some(worth) = game.worth {
+            fields.push(EmbedField {
+                name: "Worth",
+                value: format!("${:.2}", worth),
+                inline: true,
+            });
+        }
+
+        // Discord renders these in the reader's timezone, as "in 2 days"
+        if let Some(end_date) = game.end_date {
+            fields.push(EmbedField {
+                name: "Ends",
+                value: format!("<t:{}:R>", end_date.timestamp()),
+                inline: true,
+            });
+        }
+
+        let image = Some(game.get_image_url())
+            .filter(|image| !image.is_empty())
+            .map(|url| EmbedImage { url });
+
+        WebhookMessage {
+            username: &self.username,
+            embeds: [Embed {
+                title: &notification.title,
+                url,
+                description: format!("[Claim on {}]({})", game.platform.label(), url),
+                color: EMBED_COLOR,
+                fields,
+                image,
+            }],
+        }
+    }
+
+    /// Remembers an exhausted bucket, so the next message doesn't run straight into a 429.
+    async fn track_bucket(&self, response: &Response) {
+        let header = |name| response.headers().get(name).and_then(|value| value.to_str().ok());
+
+        if header("x-ratelimit-remaining") != Some("0") {
+            return;
+        }
+
+        let reset_after = header("x-ratelimit-reset-after")
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|seconds| seconds.is_finite() && *seconds >= 0.0);
+
+        if let Some(reset_after) = reset_after {
+            let wait = Duration::from_secs_f64(reset_after).min(MAX_RETRY_WAIT);
+            *self.blocked_until.lock().await = Some(Instant::now() + wait);
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for DiscordSink {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let message = self.build_message(notification);
+
+        if let Some(blocked_until) = self.blocked_until.lock().await.take() {
+            sleep_until(blocked_until).await;
+        }
+
+        let response = request_with_retries("Discord", || {
+            http::client().post(&self.webhook_url).json(&message)
+        }).await?;
+
+        self.track_bucket(&response).await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::sinks::tests::{notification, received_json};
+
+    #[tokio::test]
+    async fn posts_an_embed() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let sink = DiscordSink::new(&format!("{}/api/webhooks/1/token", server.uri()), "FreeTrayGames");
+        sink.send(&notification()).await.unwrap();
+
+        assert_eq!(received_json(&server).await, [json!({
+            "username": "FreeTrayGames",
+            "embeds": [{
+                "title": "Hollow <Lantern> & Friends (Epic Games Store)",
+                "url": "https://store.example/p/hollow-lantern",
+                "description": "[Claim on Epic Games Store](https://store.example/p/hollow-lantern)",
+                "color": EMBED_COLOR,
+                "fields": [
+                    { "name": "Platform", "value": "Epic Games Store", "inline": true },
+                    { "name": "Worth", "value": "$24.99", "inline": true },
+                    { "name": "Ends", "value": "<t:1760022000:R>", "inline": true },
+                ],
+                "image": { "url": "https://cdn.example/hollow.jpg" },
+            }],
+        })]);
+    }
+
+    #[tokio::test]
+    async fn retries_after_rate_limit() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429)
+                .insert_header("Retry-After", "0.1")
+                .set_body_string(r#"{"message": "You are being rate limited.", "retry_after": 0.1, "global": false}"#))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let sink = DiscordSink::new(&server.uri(), "FreeTrayGames");
+        sink.send(&notification()).await.unwrap();
+
+        assert_eq!(received_json(&server).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn waits_for_an_exhausted_bucket() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204)
+                .insert_header("X-RateLimit-Remaining", "0")
+                .insert_header("X-RateLimit-Reset-After", "0.3"))
+            .mount(&server)
+            .await;
+
+        let sink = DiscordSink::new(&server.uri(), "FreeTrayGames");
+        sink.send(&notification()).await.unwrap();
+
+        let started = Instant::now();
+        sink.send(&notification()).await.unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(250));
+    }
+}
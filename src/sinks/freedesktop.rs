@@ -0,0 +1,288 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use tokio::sync::OnceCell;
+use zbus::{connection, proxy, zvariant::Value, Connection};
+
+use super::{download_image, escape_html, Notification, NotificationSink};
+
+const APP_NAME: &str = "FreeTrayGames";
+const DEFAULT_ACTION: &str = "default";
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
+
+/// Open notification ids with the URL their default action should open.
+type PendingMap = Arc<Mutex<HashMap<u32, String>>>;
+
+/// Opens the URL of a clicked notification.
+type Opener = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// `org.freedesktop.Notifications` on the session bus from DBUS_SESSION_BUS_ADDRESS.
+/// Clicking a notification opens the offer.
+pub struct FreedesktopSink {
+    /// Bus to use instead of the session one.
+    bus_address: Option<String>,
+    opener: Opener,
+    proxy: OnceCell<NotificationsProxy<'static>>,
+    pending: PendingMap,
+}
+
+impl FreedesktopSink {
+    pub fn new() -> Self {
+        Self::with_bus(None, Arc::new(|url| {
+            let _ = open::that(url);
+        }))
+    }
+
+    pub fn with_bus(bus_address: Option<&str>, opener: Opener) -> Self {
+        FreedesktopSink {
+            bus_address: bus_address.map(str::to_string),
+            opener,
+            proxy: OnceCell::new(),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Connects on first use, so a missing notification server doesn't stop the app from starting.
+    async fn get_proxy(&self) -> Result<&NotificationsProxy<'static>> {
+        self.proxy.get_or_try_init(|| async {
+            let connection = match &self.bus_address {
+                Some(address) => connection::Builder::address(address.as_str())?.build().await,
+                None => Connection::session().await,
+            }.context("Failed to connect to the session bus")?;
+
+            let proxy = NotificationsProxy::new(&connection).await
+                .context("Failed to reach the notification server")?;
+
+            Self::listen_for_actions(&proxy, Arc::clone(&self.pending), Arc::clone(&self.opener)).await?;
+
+            Ok(proxy)
+        }).await
+    }
+
+    async fn listen_for_actions(proxy: &NotificationsProxy<'static>, pending: PendingMap, opener: Opener) -> Result<()> {
+        let mut invoked = proxy.receive_action_invoked().await.context("Failed to subscribe to actions")?;
+        let mut closed = proxy.receive_notification_closed().await.context("Failed to subscribe to closing")?;
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(signal) = invoked.next() => {
+                        let Ok(args) = signal.args() else { continue };
+
+                        if args.action_key != DEFAULT_ACTION {
+                            continue;
+                        }
+
+                        let url = pending.lock().ok().and_then(|mut p| p.remove(&args.id));
+
+                        if let Some(url) = url {
+                            opener(&url);
+                        }
+                    }
+                    Some(signal) = closed.next() => {
+                        let Ok(args) = signal.args() else { continue };
+
+                        if let Ok(mut pending) = pending.lock() {
+                            pending.remove(&args.id);
+                        }
+                    }
+                    else => break,
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationSink for FreedesktopSink {
+    fn name(&self) -> &'static str {
+        "freedesktop"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let game = &notification.game;
+        let image_path = download_image(game.get_image_url()).await.context("Failed to download image")?;
+
+        let mut hints = HashMap::new();
+
+        if !image_path.is_empty() {
+            hints.insert("image-path", Value::from(image_path.as_str()));
+        }
+
+        let proxy = self.get_proxy().await?;
+
+        let id = proxy.notify(
+            APP_NAME,
+            0,
+            "",
+            &notification.title,
+            // Servers may read the body as markup
+            &escape_html(&notification.message),
+            &[DEFAULT_ACTION, "Claim"],
+            hints,
+            -1,
+        ).await.context("Failed to show notification")?;
+
+        self.pending.lock()
+            .map_err(|_| anyhow!("Pending notifications are poisoned"))?
+            .insert(id, game.get_open_url().to_string());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+    use zbus::{interface, zvariant::OwnedValue};
+
+    use super::*;
+    use crate::{sinks::tests::notification, test_bus::private_session_bus};
+
+    const SERVER_NAME: &str = "org.freedesktop.Notifications";
+    const SERVER_PATH: &str = "/org/freedesktop/Notifications";
+
+    struct Shown {
+        app_name: String,
+        summary: String,
+        body: String,
+        actions: Vec<String>,
+        hints: HashMap<String, OwnedValue>,
+    }
+
+    /// Stands in for the desktop's notification daemon.
+    struct NotificationServer {
+        shown: Arc<Mutex<Vec<Shown>>>,
+    }
+
+    #[interface(name = "org.freedesktop.Notifications")]
+    impl NotificationServer {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            app_name: String,
+            _replaces_id: u32,
+            _app_icon: String,
+            summary: String,
+            body: String,
+            actions: Vec<String>,
+            hints: HashMap<String, OwnedValue>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            let mut shown = self.shown.lock().unwrap();
+            shown.push(Shown { app_name, summary, body, actions, hints });
+
+            shown.len() as u32
+        }
+    }
+
+    async fn wait_until(condition: impl Fn() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !condition() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("timed out waiting for the signal to be handled");
+    }
+
+    #[tokio::test]
+    async fn opens_clicked_offers_and_forgets_closed_ones() {
+        let Some(address) = private_session_bus() else {
+            return;
+        };
+
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        let server = connection::Builder::address(address)
+            .and_then(|b| b.name(SERVER_NAME))
+            .and_then(|b| b.serve_at(SERVER_PATH, NotificationServer { shown: Arc::clone(&shown) }))
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let images = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/covers/hollow.png"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(b"png bytes".to_vec(), "image/png"))
+            .expect(1)
+            .mount(&images)
+            .await;
+
+        let mut notification = notification();
+        notification.game.image = format!("{}/covers/hollow.png", images.uri());
+        notification.message = "Worth $24.99 & free for <today>".to_string();
+
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        let opener_log = Arc::clone(&opened);
+        let sink = FreedesktopSink::with_bus(Some(address), Arc::new(move |url| {
+            opener_log.lock().unwrap().push(url.to_string());
+        }));
+
+        sink.send(&notification).await.unwrap();
+        sink.send(&notification).await.unwrap();
+
+        {
+            let shown = shown.lock().unwrap();
+            let [shown, _] = shown.as_slice() else { panic!("expected two notifications") };
+
+            assert_eq!(shown.app_name, APP_NAME);
+            assert_eq!(shown.summary, "Hollow <Lantern> & Friends (Epic Games Store)");
+            assert_eq!(shown.body, "Worth $24.99 &amp; free for &lt;today&gt;");
+            assert_eq!(shown.actions, [DEFAULT_ACTION, "Claim"]);
+
+            let image_path = String::try_from(shown.hints["image-path"].try_clone().unwrap()).unwrap();
+            assert_eq!(fs::read(image_path).unwrap(), b"png bytes");
+        }
+
+        assert_eq!(
+            sink.pending.lock().unwrap().get(&1).map(String::as_str),
+            Some("https://store.example/p/hollow-lantern"),
+        );
+
+        // The first one is dismissed, the second one clicked
+        server.emit_signal(None::<()>, SERVER_PATH, SERVER_NAME, "NotificationClosed", &(1u32, 2u32))
+            .await
+            .unwrap();
+        wait_until(|| !sink.pending.lock().unwrap().contains_key(&1)).await;
+
+        server.emit_signal(None::<()>, SERVER_PATH, SERVER_NAME, "ActionInvoked", &(2u32, DEFAULT_ACTION))
+            .await
+            .unwrap();
+        wait_until(|| !opened.lock().unwrap().is_empty()).await;
+
+        assert_eq!(*opened.lock().unwrap(), ["https://store.example/p/hollow-lantern"]);
+        assert!(sink.pending.lock().unwrap().is_empty());
+    }
+}
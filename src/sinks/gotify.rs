@@ -0,0 +1,188 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use super::{send_with_retries, Notification, NotificationSink};
+use crate::http;
+
+#[derive(Serialize)]
+struct GotifyMessage<'a> {
+    title: &'a str,
+    message: String,
+    priority: u8,
+    extras: Extras<'a>,
+}
+
+#[derive(Serialize)]
+struct Extras<'a> {
+    #[serde(rename = "client::display")]
+    display: Display,
+    #[serde(rename = "client::notification")]
+    notification: ClientNotification<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Display {
+    content_type: &'static str,
+}
+
+#[derive(Serialize)]
+struct ClientNotification<'a> {
+    click: Click<'a>,
+}
+
+#[derive(Serialize)]
+struct Click<'a> {
+    url: &'a str,
+}
+
+/// Sends every notification as a markdown message to a Gotify server application.
+pub struct GotifySink {
+    message_url: String,
+    app_token: String,
+    priority: u8,
+}
+
+impl GotifySink {
+    pub fn new(server_url: &str, app_token: &str, priority: u8) -> Self {
+        GotifySink {
+            message_url: format!("{}/message", server_url.trim_end_matches('/')),
+            app_token: app_token.to_string(),
+            priority,
+        }
+    }
+
+    fn build_markdown(notification: &Notification) -> String {
+        let game = &notification.game;
+        let mut lines: Vec<String> = notification.details()
+            .into_iter()
+            .map(|(name, value)| format!("**{}:** {}  ", name, escape_markdown(&value)))
+            .collect();
+
+        lines.push(String::new());
+        lines.push(format!("[Claim]({})", game.get_open_url()));
+
+        let image_url = game.get_image_url();
+
+        if !image_url.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("![{}]({})", escape_markdown(&game.title), image_url));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Backslash-escapes the characters that would otherwise turn offer text into emphasis, links or HTML.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '(' | ')' | '<' | '>' | '#' | '!' | '~' | '|') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[async_trait]
+impl NotificationSink for GotifySink {
+    fn name(&self) -> &'static str {
+        "gotify"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let message = GotifyMessage {
+            title: &notification.title,
+            message: Self::build_markdown(notification),
+            priority: self.priority,
+            extras: Extras {
+                display: Display {
+                    content_type: "text/markdown",
+                },
+                notification: ClientNotification {
+                    click: Click {
+                        url: notification.game.get_open_url(),
+                    },
+                },
+            },
+        };
+
+        send_with_retries("Gotify", || {
+            http::client().post(&self.message_url)
+                .header("X-Gotify-Key", &self.app_token)
+                .json(&message)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::sinks::tests::{notification, received_json};
+
+    #[tokio::test]
+    async fn posts_markdown_message_with_app_token() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/gotify/message"))
+            .and(header("X-Gotify-Key", "A1b2C3"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"id": 7}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        GotifySink::new(&format!("{}/gotify/", server.uri()), "A1b2C3", 6)
+            .send(&notification())
+            .await
+            .unwrap();
+
+        assert_eq!(received_json(&server).await, [json!({
+            "title": "Hollow <Lantern> & Friends (Epic Games Store)",
+            "message": "**Platform:** Epic Games Store  \n\
+                        **Worth:** $24.99  \n\
+                        **Ends:** Oct 9, 15:00 UTC  \n\
+                        \n\
+                        [Claim](https://store.example/p/hollow-lantern)\n\
+                        \n\
+                        ![Hollow \\<Lantern\\> & Friends](https://cdn.example/hollow.jpg)",
+            "priority": 6,
+            "extras": {
+                "client::display": { "contentType": "text/markdown" },
+                "client::notification": { "click": { "url": "https://store.example/p/hollow-lantern" } },
+            },
+        })]);
+    }
+
+    #[tokio::test]
+    async fn reports_a_wrong_token() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401).set_body_string(r#"{"error": "Unauthorized"}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let error = GotifySink::new(&server.uri(), "wrong", 5).send(&notification()).await.unwrap_err();
+
+        assert_eq!(format!("{:#}", error), r#"Gotify responded with 401 Unauthorized: {"error": "Unauthorized"}"#);
+    }
+
+    #[test]
+    fn escapes_markdown_in_titles() {
+        assert_eq!(escape_markdown("*Star_Fall* [Deluxe] (2024)"), r"\*Star\_Fall\* \[Deluxe\] \(2024\)");
+        assert_eq!(escape_markdown("Hollow Lantern: Act 2"), "Hollow Lantern: Act 2");
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{Notification, NotificationSink};
+
+pub struct LogSink;
+
+impl LogSink {
+    pub fn new() -> Self {
+        LogSink
+    }
+}
+
+#[async_trait]
+impl NotificationSink for LogSink {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        tracing::info!(
+            "{}: {} ({})",
+            notification.title,
+            notification.message,
+            notification.game.get_open_url(),
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{header::CONTENT_TYPE, Url};
+use serde::{Deserialize, Serialize};
+
+use super::{escape_html, send_with_retries, Notification, NotificationSink};
+use crate::http;
+
+#[derive(Serialize)]
+struct RoomMessage {
+    msgtype: &'static str,
+    /// Plain text fallback for clients without HTML support.
+    body: String,
+    format: &'static str,
+    formatted_body: String,
+}
+
+#[derive(Deserialize)]
+struct Uploaded {
+    content_uri: String,
+}
+
+/// Posts every notification as an HTML message to a Matrix room, with the cover uploaded to the homeserver.
+pub struct MatrixSink {
+    homeserver_url: String,
+    access_token: String,
+    room_id: String,
+    upload_images: bool,
+}
+
+impl MatrixSink {
+    pub fn new(homeserver_url: &str, access_token: &str, room_id: &str, upload_images: bool) -> Self {
+        MatrixSink {
+            homeserver_url: homeserver_url.trim_end_matches('/').to_string(),
+            access_token: access_token.to_string(),
+            room_id: room_id.to_string(),
+            upload_images,
+        }
+    }
+
+    fn endpoint(&self, segments: &[&str]) -> Result<Url> {
+        let mut url = Url::parse(&self.homeserver_url).context("Invalid Matrix homeserver URL")?;
+
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid Matrix homeserver URL"))?
+            .pop_if_empty()
+            .extend(segments);
+
+        Ok(url)
+    }
+
+    /// Re-hosts the cover on the homeserver, clients only show `mxc://` images inline.
+    async fn upload_image(&self, image_url: &str) -> Result<String> {
+        let image = http::client().get(image_url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .context("Failed to download image")?;
+
+        let content_type = image.headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("image/jpeg")
+            .to_string();
+        let content = image.bytes().await.context("Failed to read image content")?;
+
+        let mut url = self.endpoint(&["_matrix", "media", "v3", "upload"])?;
+        url.query_pairs_mut().append_pair("filename", image_url.rsplit('/').next().unwrap_or("cover"));
+
+        let response = http::client().post(url)
+            .bearer_auth(&self.access_token)
+            .header(CONTENT_TYPE, content_type)
+            .body(content)
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)
+            .context("Failed to reach Matrix homeserver")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("Matrix upload responded with {}: {}", status, body);
+        }
+
+        let uploaded: Uploaded = response.json().await.context("Failed to parse Matrix upload response")?;
+
+        Ok(uploaded.content_uri)
+    }
+
+    fn build_message(notification: &Notification, image: Option<&str>) -> RoomMessage {
+        let game = &notification.game;
+        let url = game.get_open_url();
+        let details = notification.details();
+
+        let mut body = vec![notification.title.clone()];
+        body.extend(details.iter().map(|(name, value)| format!("{}: {}", name, value)));
+        body.push(format!("Claim: {}", url));
+
+        let mut html = format!(
+            "<h4><a href=\"{}\">{}</a></h4><p>",
+            escape_html(url),
+            escape_html(&notification.title),
+        );
+
+        for (name, value) in &details {
+            html.push_str(&format!("<b>{}:</b> {}<br>", name, escape_html(value)));
+        }
+
+        html.push_str(&format!("<a href=\"{}\">Claim</a></p>", escape_html(url)));
+
+        if let Some(image) = image {
+            html.push_str(&format!("<img src=\"{}\" alt=\"{}\" height=\"200\">", escape_html(image), escape_html(&game.title)));
+        }
+
+        RoomMessage {
+            msgtype: "m.text",
+            body: body.join("\n"),
+            format: "org.matrix.custom.html",
+            formatted_body: html,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for MatrixSink {
+    fn name(&self) -> &'static str {
+        "matrix"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let image_url = notification.game.get_image_url();
+
+        // A missing cover shouldn't cost the whole message
+        let image = if self.upload_images && !image_url.is_empty() {
+            self.upload_image(image_url)
+                .await
+                .inspect_err(|e| tracing::warn!("Sending Matrix message without cover: {:#}", e))
+                .ok()
+        } else {
+            None
+        };
+
+        let message = Self::build_message(notification, image.as_deref());
+
+        // Retries reuse the transaction id, so the homeserver won't post the message twice
+        let txn_id = format!("ftg-{}-{}", notification.game.id, Utc::now().timestamp_millis());
+        let url = self.endpoint(&["_matrix", "client", "v3", "rooms", &self.room_id, "send", "m.room.message", &txn_id])?;
+
+        send_with_retries("Matrix", || {
+            http::client().put(url.clone())
+                .bearer_auth(&self.access_token)
+                .json(&message)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        matchers::{body_bytes, header, method, path, path_regex, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::sinks::tests::notification;
+
+    const SEND_PATH: &str = r"^/_matrix/client/v3/rooms/!room:local/send/m\.room\.message/ftg-42-\d+$";
+
+    async fn mount_send(server: &MockServer) {
+        Mock::given(method("PUT"))
+            .and(path_regex(SEND_PATH))
+            .and(header("Authorization", "Bearer syt_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"event_id": "$event"}"#))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    async fn sent_message(server: &MockServer) -> serde_json::Value {
+        server.received_requests().await
+            .unwrap()
+            .iter()
+            .find(|request| request.method.as_str() == "PUT")
+            .map(|request| request.body_json().unwrap())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn uploads_the_cover_and_sends_html() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/covers/hollow.png"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(b"png bytes".to_vec(), "image/png"))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/_matrix/media/v3/upload"))
+            .and(query_param("filename", "hollow.png"))
+            .and(header("Content-Type", "image/png"))
+            .and(body_bytes(b"png bytes".to_vec()))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"content_uri": "mxc://local/cover"}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        mount_send(&server).await;
+
+        let mut notification = notification();
+        notification.game.image = format!("{}/covers/hollow.png", server.uri());
+
+        MatrixSink::new(&server.uri(), "syt_token", "!room:local", true).send(&notification).await.unwrap();
+
+        let message = sent_message(&server).await;
+
+        assert_eq!(message["msgtype"], "m.text");
+        assert_eq!(message["format"], "org.matrix.custom.html");
+        assert_eq!(message["body"], "Hollow <Lantern> & Friends (Epic Games Store)\n\
+                                     Platform: Epic Games Store\n\
+                                     Worth: $24.99\n\
+                                     Ends: Oct 9, 15:00 UTC\n\
+                                     Claim: https://store.example/p/hollow-lantern");
+        assert_eq!(message["formatted_body"], "<h4><a href=\"https://store.example/p/hollow-lantern\">Hollow &lt;Lantern&gt; &amp; Friends (Epic Games Store)</a></h4>\
+                                               <p><b>Platform:</b> Epic Games Store<br><b>Worth:</b> $24.99<br><b>Ends:</b> Oct 9, 15:00 UTC<br>\
+                                               <a href=\"https://store.example/p/hollow-lantern\">Claim</a></p>\
+                                               <img src=\"mxc://local/cover\" alt=\"Hollow &lt;Lantern&gt; &amp; Friends\" height=\"200\">");
+    }
+
+    #[tokio::test]
+    async fn sends_without_cover_when_upload_fails() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/covers/hollow.png"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        mount_send(&server).await;
+
+        let mut notification = notification();
+        notification.game.image = format!("{}/covers/hollow.png", server.uri());
+
+        MatrixSink::new(&server.uri(), "syt_token", "!room:local", true).send(&notification).await.unwrap();
+
+        let message = sent_message(&server).await;
+
+        assert!(!message["formatted_body"].as_str().unwrap().contains("<img"));
+    }
+
+    #[tokio::test]
+    async fn skips_upload_when_turned_off() {
+        let server = MockServer::start().await;
+
+        mount_send(&server).await;
+
+        MatrixSink::new(&server.uri(), "syt_token", "!room:local", false).send(&notification()).await.unwrap();
+
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+}
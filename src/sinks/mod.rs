@@ -0,0 +1,450 @@
+mod discord;
+#[cfg(target_os = "linux")]
+mod freedesktop;
+mod gotify;
+mod log;
+mod matrix;
+mod ntfy;
+mod slack;
+mod stdout;
+mod teams;
+mod telegram;
+#[cfg(windows)]
+mod toast;
+mod webhook;
+
+use std::{env, fs::{create_dir_all, File}, time::Duration};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{header::RETRY_AFTER, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+
+use crate::{config::SinksConfig, games::{Game, GiveawayType}, http, platform::Platform};
+
+pub use discord::DiscordSink;
+#[cfg(target_os = "linux")]
+pub use freedesktop::FreedesktopSink;
+pub use gotify::GotifySink;
+pub use log::LogSink;
+pub use matrix::MatrixSink;
+pub use ntfy::NtfySink;
+pub use slack::SlackSink;
+pub use stdout::StdoutSink;
+pub use teams::TeamsSink;
+pub use telegram::TelegramSink;
+#[cfg(windows)]
+pub use toast::ToastSink;
+pub use webhook::WebhookSink;
+
+/// A single message about an offer. Sinks decide on their own how much of the game to show.
+pub struct Notification {
+    pub title: String,
+    pub message: String,
+    pub game: Game,
+}
+
+/// Machine readable form of a notification, shared by the sinks speaking JSON.
+#[derive(Debug, Serialize)]
+pub struct NotificationPayload<'a> {
+    pub title: &'a str,
+    pub message: &'a str,
+    pub id: usize,
+    pub game: &'a str,
+    pub platform: Platform,
+    #[serde(rename = "type")]
+    pub game_type: GiveawayType,
+    pub url: &'a str,
+    pub image: &'a str,
+    pub worth: Option<f64>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+impl Notification {
+    /// Short labelled facts about the offer for sinks that lay them out as a list.
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        let mut details = vec![("Platform", self.game.platform.label().to_string())];
+
+        if let Some(worth) = self.game.worth {
+            details.push(("Worth", format!("${:.2}", worth)));
+        }
+
+        if let Some(end_date) = self.game.end_date {
+            details.push(("Ends", end_date.format("%b %-d, %H:%M UTC").to_string()));
+        }
+
+        details
+    }
+
+    pub fn payload(&self) -> NotificationPayload<'_> {
+        NotificationPayload {
+            title: &self.title,
+            message: &self.message,
+            id: self.game.id,
+            game: &self.game.title,
+            platform: self.game.platform,
+            game_type: self.game.game_type,
+            url: self.game.get_open_url(),
+            image: self.game.get_image_url(),
+            worth: self.game.worth,
+            end_date: self.game.end_date,
+        }
+    }
+}
+
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn send(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Sinks turned on in the config. Headless mode has no desktop to show notifications on,
+/// so it swaps the desktop sink for stdout.
+pub fn enabled_sinks(config: &SinksConfig, is_headless: bool) -> Vec<Box<dyn NotificationSink>> {
+    let mut sinks: Vec<Box<dyn NotificationSink>> = Vec::new();
+
+    if config.desktop && !is_headless {
+        sinks.push(desktop_sink());
+    }
+
+    if config.stdout && is_headless {
+        sinks.push(Box::new(StdoutSink::new()));
+    }
+
+    if config.log {
+        sinks.push(Box::new(LogSink::new()));
+    }
+
+    for url in &config.webhooks {
+        sinks.push(Box::new(WebhookSink::new(url)));
+    }
+
+    if config.discord.enabled && !config.discord.webhook_url.is_empty() {
+        sinks.push(Box::new(DiscordSink::new(&config.discord.webhook_url, &config.discord.username)));
+    }
+
+    if config.slack.enabled && !config.slack.webhook_url.is_empty() {
+        sinks.push(Box::new(SlackSink::new(&config.slack.webhook_url)));
+    }
+
+    if config.teams.enabled && !config.teams.webhook_url.is_empty() {
+        sinks.push(Box::new(TeamsSink::new(&config.teams.webhook_url)));
+    }
+
+    if config.ntfy.enabled && !config.ntfy.topic_url.is_empty() {
+        match NtfySink::new(&config.ntfy.topic_url, config.ntfy.priority, config.ntfy.token.as_deref()) {
+            Ok(sink) => sinks.push(Box::new(sink)),
+            Err(error) => tracing::warn!("Skipping ntfy notifications: {:#}", error),
+        }
+    }
+
+    let gotify = &config.gotify;
+
+    if gotify.enabled && !gotify.server_url.is_empty() {
+        if gotify.app_token.is_empty() {
+            tracing::warn!("Skipping Gotify notifications: no app_token is set");
+        } else {
+            sinks.push(Box::new(GotifySink::new(&gotify.server_url, &gotify.app_token, gotify.priority)));
+        }
+    }
+
+    let telegram = &config.telegram;
+
+    if telegram.enabled && !telegram.bot_token.is_empty() && !telegram.chat_id.is_empty() {
+        sinks.push(Box::new(TelegramSink::new(&telegram.api_url, &telegram.bot_token, &telegram.chat_id)));
+    }
+
+    let matrix = &config.matrix;
+
+    if matrix.enabled && !matrix.homeserver_url.is_empty() && !matrix.room_id.is_empty() {
+        sinks.push(Box::new(MatrixSink::new(
+            &matrix.homeserver_url,
+            &matrix.access_token,
+            &matrix.room_id,
+            matrix.upload_images,
+        )));
+    }
+
+    sinks
+}
+
+#[cfg(windows)]
+fn desktop_sink() -> Box<dyn NotificationSink> {
+    Box::new(ToastSink::new())
+}
+
+#[cfg(target_os = "linux")]
+fn desktop_sink() -> Box<dyn NotificationSink> {
+    Box::new(FreedesktopSink::new())
+}
+
+/// Attempts made by `send_with_retries` before giving up.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Longest a sink waits for a rate limit, every other sink and the tray wait along with it.
+pub(crate) const MAX_RETRY_WAIT: Duration = Duration::from_secs(60);
+
+/// Sends the request built by `build`, retrying network errors, 429 and 5xx responses
+/// with a doubling delay. `service` names the other end in errors.
+pub(crate) async fn send_with_retries<F>(service: &str, build: F) -> Result<()>
+where
+    F: Fn() -> RequestBuilder,
+{
+    request_with_retries(service, build).await.map(drop)
+}
+
+/// Same as `send_with_retries`, but hands back the successful response for a closer look.
+pub(crate) async fn request_with_retries<F>(service: &str, build: F) -> Result<Response>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut delay = Duration::from_secs(1);
+    let mut attempt = 1;
+
+    loop {
+        let is_last = attempt == MAX_ATTEMPTS;
+
+        // Some services keep the credentials in the URL, so it's left out of errors
+        let wait = match build().send().await.map_err(reqwest::Error::without_url) {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => {
+                let status = response.status();
+
+                if is_last || !(status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) {
+                    let body = response.text().await.unwrap_or_default();
+                    bail!("{} responded with {}: {}", service, status, body);
+                }
+
+                // The server knows best when it's ready again, within reason
+                let wait = get_retry_after(&response).unwrap_or(delay);
+
+                if wait > MAX_RETRY_WAIT {
+                    tracing::warn!("{} asked to wait {:.0}s before retrying, giving up", service, wait.as_secs_f64());
+                    bail!("{} responded with {} and asked to wait {:.0}s", service, status, wait.as_secs_f64());
+                }
+
+                tracing::info!("{} responded with {}, retrying in {:.1}s", service, status, wait.as_secs_f64());
+                wait
+            }
+            Err(e) if is_last => {
+                return Err(e).with_context(|| format!("Failed to reach {}", service));
+            }
+            Err(e) => {
+                tracing::info!("Failed to reach {} ({}), retrying in {}s", service, e, delay.as_secs());
+                delay
+            }
+        };
+
+        tokio::time::sleep(wait).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
+/// Reads `Retry-After` given in seconds, Discord sends fractions of them.
+fn get_retry_after(response: &Response) -> Option<Duration> {
+    response.headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// Escapes text placed in the HTML subsets understood by Telegram, Matrix and desktop notification bodies.
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Caches the cover in the temp directory and returns its path, or an empty string when there is none.
+pub(crate) async fn download_image(url: &str) -> Result<String> {
+    if url.is_empty() {
+        return Ok("".to_string());
+    }
+
+    let mut temp_dir = env::temp_dir();
+    temp_dir.push("free_tray_games");
+
+    create_dir_all(temp_dir.clone()).context("Failed to create temp directory for images")?;
+
+    let hash_name = format!("{:x}", md5::compute(url));
+
+    let ext = url.split('.').next_back().unwrap_or("jpg");
+    let filename = format!("{}.{}", hash_name, ext);
+
+    let mut file_path = temp_dir.clone();
+    file_path.push(filename);
+
+    if file_path.exists() {
+        return Ok(file_path.to_str().unwrap().to_string());
+    }
+
+    let response = http::client().get(url).send().await.context("Failed to download image")?;
+    if !response.status().is_success() {
+        return Ok("".to_string());
+    }
+
+    let content = response.bytes().await.context("Failed to read image content")?;
+    let mut file = File::create(file_path.clone()).context("Failed to create image file")?;
+    std::io::copy(&mut content.as_ref(), &mut file).context("Failed to write image content")?;
+
+    Ok(file_path.to_str().unwrap().to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use wiremock::{
+        matchers::{body_partial_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    /// The offer every sink test sends, with characters that need escaping in its title.
+    pub(crate) fn notification() -> Notification {
+        let game = Game {
+            id: 42,
+            title: "Hollow <Lantern> & Friends".to_string(),
+            platform: Platform::Epic,
+            open_giveaway_url: "https://store.example/p/hollow-lantern".to_string(),
+            image: "https://cdn.example/hollow.jpg".to_string(),
+            worth: Some(24.99),
+            end_date: "2025-10-09T15:00:00Z".parse().ok(),
+            ..Default::default()
+        };
+
+        Notification {
+            title: format!("{} (Epic Games Store)", game.title),
+            message: "Worth $24.99, free until Oct 9, 15:00 UTC. Click to claim".to_string(),
+            game,
+        }
+    }
+
+    /// Bodies of the requests the stub got so far, in order.
+    pub(crate) async fn received_json(server: &MockServer) -> Vec<serde_json::Value> {
+        server.received_requests().await
+            .unwrap_or_default()
+            .iter()
+            .map(|request| request.body_json().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn webhook_posts_payload() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(body_partial_json(serde_json::json!({
+                "title": "Hollow <Lantern> & Friends (Epic Games Store)",
+                "id": 42,
+                "game": "Hollow <Lantern> & Friends",
+                "platform": "epic",
+                "type": "game",
+                "url": "https://store.example/p/hollow-lantern",
+                "image": "https://cdn.example/hollow.jpg",
+                "worth": 24.99,
+                "end_date": "2025-10-09T15:00:00Z",
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        WebhookSink::new(&format!("{}/hook", server.uri())).send(&notification()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn retries_when_the_server_is_busy() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        send_with_retries("stub", || http::client().post(server.uri())).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn gives_up_instead_of_waiting_too_long() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "86400"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let sent = tokio::time::timeout(
+            Duration::from_secs(5),
+            send_with_retries("stub", || http::client().post(server.uri())),
+        ).await.expect("waited for the rate limit");
+
+        assert_eq!(format!("{:#}", sent.unwrap_err()), "stub responded with 429 Too Many Requests and asked to wait 86400s");
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_rejected_requests_without_naming_the_url() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("invalid payload"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/hooks/secret-token", server.uri());
+        let error = WebhookSink::new(&url).send(&notification()).await.unwrap_err();
+
+        assert_eq!(format!("{:#}", error), "webhook responded with 400 Bad Request: invalid payload");
+    }
+
+    #[tokio::test]
+    async fn leaves_the_url_out_of_network_errors() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hooks/secret-token", listener.local_addr().unwrap());
+        drop(listener);
+
+        let error = WebhookSink::new(&url).send(&notification()).await.unwrap_err();
+
+        assert!(!format!("{:#}", error).contains("secret-token"), "{:#}", error);
+    }
+
+    #[test]
+    fn skips_sinks_missing_their_settings() {
+        let mut config = SinksConfig::default();
+        config.ntfy.enabled = true;
+        config.ntfy.topic_url = "https://ntfy.sh".to_string();
+        config.gotify.enabled = true;
+        config.gotify.server_url = "https://gotify.example".to_string();
+
+        let names: Vec<_> = enabled_sinks(&config, true).iter().map(|sink| sink.name()).collect();
+
+        assert_eq!(names, ["stdout", "log"]);
+
+        config.ntfy.topic_url = "https://ntfy.sh/free-games".to_string();
+        config.gotify.app_token = "A1b2C3".to_string();
+
+        let names: Vec<_> = enabled_sinks(&config, true).iter().map(|sink| sink.name()).collect();
+
+        assert_eq!(names, ["stdout", "log", "ntfy", "gotify"]);
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(escape_html(r#"<a href="x">Tom & Jerry</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&lt;/a&gt;");
+    }
+}
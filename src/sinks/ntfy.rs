@@ -0,0 +1,164 @@
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::Serialize;
+
+use super::{send_with_retries, Notification, NotificationSink};
+use crate::http;
+
+#[derive(Serialize)]
+struct NtfyMessage<'a> {
+    topic: &'a str,
+    title: &'a str,
+    message: &'a str,
+    priority: u8,
+    tags: [&'static str; 1],
+    /// Opened when the notification is tapped.
+    click: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attach: Option<&'a str>,
+}
+
+/// Publishes every notification to an ntfy topic, so it shows up on phones subscribed to it.
+pub struct NtfySink {
+    server_url: String,
+    topic: String,
+    priority: u8,
+    token: Option<String>,
+}
+
+impl NtfySink {
+    /// `topic_url` is the full topic address, e.g. `https://ntfy.sh/free-games`.
+    pub fn new(topic_url: &str, priority: u8, token: Option<&str>) -> Result<Self> {
+        let mut server_url = Url::parse(topic_url).context("Invalid ntfy topic URL")?;
+        let no_topic = || anyhow!("The ntfy topic URL has no topic, e.g. https://ntfy.sh/free-games");
+
+        let topic = server_url.path_segments()
+            .and_then(|mut segments| segments.rfind(|segment| !segment.is_empty()))
+            .ok_or_else(no_topic)?
+            .to_string();
+
+        // The topic is the last path segment, anything before it is where the server is hosted
+        server_url.path_segments_mut()
+            .map_err(|_| no_topic())?
+            .pop_if_empty()
+            .pop();
+        server_url.set_query(None);
+        server_url.set_fragment(None);
+
+        Ok(NtfySink {
+            server_url: server_url.to_string(),
+            topic,
+            priority: priority.clamp(1, 5),
+            token: token.map(str::to_string),
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationSink for NtfySink {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let image_url = notification.game.get_image_url();
+
+        // Publishing as JSON to the server root keeps non-ASCII titles out of headers
+        let message = NtfyMessage {
+            topic: &self.topic,
+            title: &notification.title,
+            message: &notification.message,
+            priority: self.priority,
+            tags: ["video_game"],
+            click: notification.game.get_open_url(),
+            attach: Some(image_url).filter(|url| !url.is_empty()),
+        };
+
+        send_with_retries("ntfy", || {
+            let request = http::client().post(&self.server_url).json(&message);
+
+            match &self.token {
+                Some(token) => request.bearer_auth(token),
+                None => request,
+            }
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::sinks::tests::{notification, received_json};
+
+    #[tokio::test]
+    async fn publishes_json_to_the_server_root() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .and(header("Authorization", "Bearer tk_secret"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        NtfySink::new(&format!("{}/free-games/", server.uri()), 4, Some("tk_secret"))
+            .unwrap()
+            .send(&notification())
+            .await
+            .unwrap();
+
+        assert_eq!(received_json(&server).await, [json!({
+            "topic": "free-games",
+            "title": "Hollow <Lantern> & Friends (Epic Games Store)",
+            "message": "Worth $24.99, free until Oct 9, 15:00 UTC. Click to claim",
+            "priority": 4,
+            "tags": ["video_game"],
+            "click": "https://store.example/p/hollow-lantern",
+            "attach": "https://cdn.example/hollow.jpg",
+        })]);
+    }
+
+    #[tokio::test]
+    async fn clamps_priority_and_skips_auth_without_token() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        NtfySink::new(&format!("{}/free-games", server.uri()), 9, None)
+            .unwrap()
+            .send(&notification())
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+
+        assert!(!requests[0].headers.contains_key("Authorization"));
+        assert_eq!(requests[0].body_json::<serde_json::Value>().unwrap()["priority"], 5);
+    }
+
+    #[test]
+    fn splits_the_topic_from_the_server_address() {
+        let sink = NtfySink::new("https://push.example/ntfy/free-games?auth=x", 3, None).unwrap();
+
+        assert_eq!(sink.server_url, "https://push.example/ntfy");
+        assert_eq!(sink.topic, "free-games");
+    }
+
+    #[test]
+    fn rejects_a_topic_url_without_topic() {
+        for topic_url in ["https://ntfy.sh", "https://ntfy.sh/", "free-games", "mailto:me@example.com"] {
+            assert!(NtfySink::new(topic_url, 3, None).is_err(), "{}", topic_url);
+        }
+    }
+}
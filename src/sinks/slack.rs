@@ -0,0 +1,172 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use super::{send_with_retries, Notification, NotificationSink};
+use crate::http;
+
+#[derive(Serialize)]
+struct SlackMessage {
+    /// Shown in push notifications and clients without Block Kit support.
+    text: String,
+    blocks: Vec<Block>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Block {
+    Section {
+        text: Text,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        accessory: Option<Element>,
+    },
+    Context {
+        elements: Vec<Text>,
+    },
+    Actions {
+        elements: Vec<Element>,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Text {
+    Mrkdwn {
+        text: String,
+    },
+    PlainText {
+        text: String,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Element {
+    Image {
+        image_url: String,
+        alt_text: String,
+    },
+    Button {
+        text: Text,
+        url: String,
+    },
+}
+
+/// Posts every notification as a Block Kit message to a Slack incoming webhook.
+pub struct SlackSink {
+    webhook_url: String,
+}
+
+impl SlackSink {
+    pub fn new(webhook_url: &str) -> Self {
+        SlackSink {
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+
+    fn build_message(notification: &Notification) -> SlackMessage {
+        let game = &notification.game;
+        let url = game.get_open_url();
+        let image_url = game.get_image_url();
+
+        let details = notification.details()
+            .into_iter()
+            .map(|(name, value)| format!("*{}:* {}", name, escape(&value)))
+            .collect::<Vec<_>>()
+            .join("  ·  ");
+
+        let accessory = (!image_url.is_empty()).then(|| Element::Image {
+            image_url: image_url.to_string(),
+            alt_text: game.title.clone(),
+        });
+
+        SlackMessage {
+            text: format!("{}: {}", notification.title, url),
+            blocks: vec![
+                Block::Section {
+                    text: Text::Mrkdwn {
+                        text: format!("*<{}|{}>*", url, escape(&notification.title)),
+                    },
+                    accessory,
+                },
+                Block::Context {
+                    elements: vec![Text::Mrkdwn { text: details }],
+                },
+                Block::Actions {
+                    elements: vec![Element::Button {
+                        text: Text::PlainText { text: "Claim".to_string() },
+                        url: url.to_string(),
+                    }],
+                },
+            ],
+        }
+    }
+}
+
+/// Slack only asks for these three to be escaped in mrkdwn.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[async_trait]
+impl NotificationSink for SlackSink {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let message = Self::build_message(notification);
+
+        send_with_retries("Slack", || http::client().post(&self.webhook_url).json(&message)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::sinks::tests::{notification, received_json};
+
+    #[tokio::test]
+    async fn posts_blocks_with_escaped_text() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .mount(&server)
+            .await;
+
+        SlackSink::new(&format!("{}/services/T0/B0/secret", server.uri())).send(&notification()).await.unwrap();
+
+        let body = &received_json(&server).await[0];
+        let field = |pointer: &str| body.pointer(pointer).and_then(|value| value.as_str()).unwrap_or_default();
+
+        assert_eq!(field("/text"), "Hollow <Lantern> & Friends (Epic Games Store): https://store.example/p/hollow-lantern");
+        assert_eq!(field("/blocks/0/text/text"), "*<https://store.example/p/hollow-lantern|Hollow &lt;Lantern&gt; &amp; Friends (Epic Games Store)>*");
+        assert_eq!(field("/blocks/0/accessory/image_url"), "https://cdn.example/hollow.jpg");
+        assert_eq!(field("/blocks/1/elements/0/text"), "*Platform:* Epic Games Store  ·  *Worth:* $24.99  ·  *Ends:* Oct 9, 15:00 UTC");
+        assert_eq!(field("/blocks/2/elements/0/type"), "button");
+        assert_eq!(field("/blocks/2/elements/0/url"), "https://store.example/p/hollow-lantern");
+    }
+
+    #[tokio::test]
+    async fn reports_rejections_without_the_webhook_url() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("invalid_token"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let error = SlackSink::new(&format!("{}/services/T0/B0/secret", server.uri()))
+            .send(&notification())
+            .await
+            .unwrap_err();
+
+        assert_eq!(format!("{:#}", error), "Slack responded with 403 Forbidden: invalid_token");
+    }
+}
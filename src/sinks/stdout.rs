@@ -0,0 +1,31 @@
+use std::io::{stdout, Write};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+use super::{Notification, NotificationSink};
+
+/// One JSON object per line, easy to pipe into other tools.
+pub struct StdoutSink;
+
+impl StdoutSink {
+    pub fn new() -> Self {
+        StdoutSink
+    }
+}
+
+#[async_trait]
+impl NotificationSink for StdoutSink {
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let line = serde_json::to_string(&notification.payload()).context("Failed to serialize notification")?;
+
+        let mut out = stdout().lock();
+        writeln!(out, "{}", line).context("Failed to write notification")?;
+        out.flush().context("Failed to flush stdout")?;
+
+        Ok(())
+    }
+}
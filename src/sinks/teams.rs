@@ -0,0 +1,215 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use super::{send_with_retries, Notification, NotificationSink};
+use crate::http;
+
+#[derive(Serialize)]
+struct TeamsMessage {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    attachments: [Attachment; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Attachment {
+    content_type: &'static str,
+    content: AdaptiveCard,
+}
+
+#[derive(Serialize)]
+struct AdaptiveCard {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    version: &'static str,
+    body: Vec<CardElement>,
+    actions: Vec<CardAction>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type")]
+enum CardElement {
+    TextBlock {
+        text: String,
+        weight: &'static str,
+        size: &'static str,
+        wrap: bool,
+    },
+    Image {
+        url: String,
+        #[serde(rename = "altText")]
+        alt_text: String,
+    },
+    FactSet {
+        facts: Vec<Fact>,
+    },
+}
+
+#[derive(Serialize)]
+struct Fact {
+    title: &'static str,
+    value: String,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type")]
+enum CardAction {
+    #[serde(rename = "Action.OpenUrl")]
+    OpenUrl {
+        title: &'static str,
+        url: String,
+    },
+}
+
+/// Posts every notification as an Adaptive Card to a Microsoft Teams incoming webhook or workflow.
+pub struct TeamsSink {
+    webhook_url: String,
+}
+
+impl TeamsSink {
+    pub fn new(webhook_url: &str) -> Self {
+        TeamsSink {
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+
+    fn build_message(notification: &Notification) -> TeamsMessage {
+        let game = &notification.game;
+        let image_url = game.get_image_url();
+
+        let mut body = vec![CardElement::TextBlock {
+            text: notification.title.clone(),
+            weight: "Bolder",
+            size: "Medium",
+            wrap: true,
+        }];
+
+        if !image_url.is_empty() {
+            body.push(CardElement::Image {
+                url: image_url.to_string(),
+                alt_text: game.title.clone(),
+            });
+        }
+
+        body.push(CardElement::FactSet {
+            facts: notification.details()
+                .into_iter()
+                .map(|(title, value)| Fact { title, value })
+                .collect(),
+        });
+
+        TeamsMessage {
+            kind: "message",
+            attachments: [Attachment {
+                content_type: "application/vnd.microsoft.card.adaptive",
+                content: AdaptiveCard {
+                    schema: "http://adaptivecards.io/schemas/adaptive-card.json",
+                    kind: "AdaptiveCard",
+                    version: "1.4",
+                    body,
+                    actions: vec![CardAction::OpenUrl {
+                        title: "Claim",
+                        url: game.get_open_url().to_string(),
+                    }],
+                },
+            }],
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for TeamsSink {
+    fn name(&self) -> &'static str {
+        "teams"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let message = Self::build_message(notification);
+
+        send_with_retries("Teams", || http::client().post(&self.webhook_url).json(&message)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::sinks::tests::{notification, received_json};
+
+    #[tokio::test]
+    async fn posts_an_adaptive_card() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(202))
+            .mount(&server)
+            .await;
+
+        TeamsSink::new(&server.uri()).send(&notification()).await.unwrap();
+
+        assert_eq!(received_json(&server).await, [json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "body": [
+                        {
+                            "type": "TextBlock",
+                            "text": "Hollow <Lantern> & Friends (Epic Games Store)",
+                            "weight": "Bolder",
+                            "size": "Medium",
+                            "wrap": true,
+                        },
+                        {
+                            "type": "Image",
+                            "url": "https://cdn.example/hollow.jpg",
+                            "altText": "Hollow <Lantern> & Friends",
+                        },
+                        {
+                            "type": "FactSet",
+                            "facts": [
+                                { "title": "Platform", "value": "Epic Games Store" },
+                                { "title": "Worth", "value": "$24.99" },
+                                { "title": "Ends", "value": "Oct 9, 15:00 UTC" },
+                            ],
+                        },
+                    ],
+                    "actions": [{
+                        "type": "Action.OpenUrl",
+                        "title": "Claim",
+                        "url": "https://store.example/p/hollow-lantern",
+                    }],
+                },
+            }],
+        })]);
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(502).insert_header("Retry-After", "0"))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(202))
+            .mount(&server)
+            .await;
+
+        TeamsSink::new(&server.uri()).send(&notification()).await.unwrap();
+
+        assert_eq!(received_json(&server).await.len(), 3);
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use super::{escape_html, send_with_retries, Notification, NotificationSink};
+use crate::http;
+
+#[derive(Serialize)]
+struct SendPhoto<'a> {
+    chat_id: &'a str,
+    photo: &'a str,
+    caption: &'a str,
+    parse_mode: &'static str,
+    reply_markup: &'a ReplyMarkup<'a>,
+}
+
+#[derive(Serialize)]
+struct SendMessage<'a> {
+    chat_id: &'a str,
+    text: &'a str,
+    parse_mode: &'static str,
+    reply_markup: &'a ReplyMarkup<'a>,
+}
+
+#[derive(Serialize)]
+struct ReplyMarkup<'a> {
+    inline_keyboard: [[InlineButton<'a>; 1]; 1],
+}
+
+#[derive(Serialize)]
+struct InlineButton<'a> {
+    text: &'static str,
+    url: &'a str,
+}
+
+/// Posts every notification to a Telegram chat or channel through a bot,
+/// as the cover with a caption when there is one.
+pub struct TelegramSink {
+    /// `{api_url}/bot{token}`, methods are appended to it.
+    bot_url: String,
+    chat_id: String,
+}
+
+impl TelegramSink {
+    pub fn new(api_url: &str, bot_token: &str, chat_id: &str) -> Self {
+        TelegramSink {
+            bot_url: format!("{}/bot{}", api_url.trim_end_matches('/'), bot_token),
+            chat_id: chat_id.to_string(),
+        }
+    }
+
+    fn build_caption(notification: &Notification) -> String {
+        let mut lines = vec![format!(
+            "<b><a href=\"{}\">{}</a></b>",
+            escape_html(notification.game.get_open_url()),
+            escape_html(&notification.title),
+        )];
+
+        lines.extend(notification.details()
+            .into_iter()
+            .map(|(name, value)| format!("<b>{}:</b> {}", name, escape_html(&value))));
+
+        lines.join("\n")
+    }
+}
+
+#[async_trait]
+impl NotificationSink for TelegramSink {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let game = &notification.game;
+        let image_url = game.get_image_url();
+        let caption = Self::build_caption(notification);
+        let reply_markup = ReplyMarkup {
+            inline_keyboard: [[InlineButton {
+                text: "Claim",
+                url: game.get_open_url(),
+            }]],
+        };
+
+        // Telegram fetches the photo from the URL itself
+        if image_url.is_empty() {
+            let message = SendMessage {
+                chat_id: &self.chat_id,
+                text: &caption,
+                parse_mode: "HTML",
+                reply_markup: &reply_markup,
+            };
+
+            send_with_retries("Telegram", || {
+                http::client().post(format!("{}/sendMessage", self.bot_url)).json(&message)
+            }).await
+        } else {
+            let message = SendPhoto {
+                chat_id: &self.chat_id,
+                photo: image_url,
+                caption: &caption,
+                parse_mode: "HTML",
+                reply_markup: &reply_markup,
+            };
+
+            send_with_retries("Telegram", || {
+                http::client().post(format!("{}/sendPhoto", self.bot_url)).json(&message)
+            }).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+    use crate::sinks::tests::{notification, received_json};
+
+    const CAPTION: &str = "<b><a href=\"https://store.example/p/hollow-lantern\">Hollow &lt;Lantern&gt; &amp; Friends (Epic Games Store)</a></b>\n\
+                           <b>Platform:</b> Epic Games Store\n\
+                           <b>Worth:</b> $24.99\n\
+                           <b>Ends:</b> Oct 9, 15:00 UTC";
+
+    async fn start_server(method_path: &str) -> MockServer {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path(method_path))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"ok": true, "result": {}}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    #[tokio::test]
+    async fn sends_the_cover_with_a_caption() {
+        let server = start_server("/bot123:ABC/sendPhoto").await;
+
+        TelegramSink::new(&format!("{}/", server.uri()), "123:ABC", "@freegames")
+            .send(&notification())
+            .await
+            .unwrap();
+
+        assert_eq!(received_json(&server).await, [json!({
+            "chat_id": "@freegames",
+            "photo": "https://cdn.example/hollow.jpg",
+            "caption": CAPTION,
+            "parse_mode": "HTML",
+            "reply_markup": { "inline_keyboard": [[{ "text": "Claim", "url": "https://store.example/p/hollow-lantern" }]] },
+        })]);
+    }
+
+    #[tokio::test]
+    async fn sends_text_without_a_cover() {
+        let server = start_server("/bot123:ABC/sendMessage").await;
+        let mut notification = notification();
+        notification.game.image.clear();
+
+        TelegramSink::new(&server.uri(), "123:ABC", "-1001234")
+            .send(&notification)
+            .await
+            .unwrap();
+
+        let body = &received_json(&server).await[0];
+
+        assert_eq!(body["chat_id"], "-1001234");
+        assert_eq!(body["text"], CAPTION);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_bot_token_out_of_errors() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_string(r#"{"ok": false, "description": "Bad Request: chat not found"}"#))
+            .mount(&server)
+            .await;
+
+        let error = TelegramSink::new(&server.uri(), "123:ABC", "@nowhere").send(&notification()).await.unwrap_err();
+
+        assert!(!format!("{:#}", error).contains("123:ABC"), "{:#}", error);
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+use super::{download_image, Notification, NotificationSink};
+use crate::{config::Config, notify_body::WinToastNotify};
+
+/// WinRT toast, clicking it opens the offer.
+pub struct ToastSink;
+
+impl ToastSink {
+    pub fn new() -> Self {
+        ToastSink
+    }
+}
+
+#[async_trait]
+impl NotificationSink for ToastSink {
+    fn name(&self) -> &'static str {
+        "toast"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let game = &notification.game;
+        let image_path = download_image(game.get_image_url()).await.context("Failed to download image")?;
+
+        WinToastNotify::new(Config::get_app_id().as_str())
+            .set_title(notification.title.as_str())
+            .set_messages(vec![notification.message.as_str()])
+            .set_image(&image_path)
+            .set_open(game.get_open_url())
+            .show()
+            .context("Failed to show notification")
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{send_with_retries, Notification, NotificationSink};
+use crate::http;
+
+/// POSTs the notification payload as JSON to any URL.
+pub struct WebhookSink {
+    url: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str) -> Self {
+        WebhookSink {
+            url: url.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let payload = notification.payload();
+
+        send_with_retries("webhook", || http::client().post(&self.url).json(&payload)).await
+    }
+}
@@ -0,0 +1,57 @@
+//! A private dbus-daemon for the D-Bus tests, so they neither need a desktop session nor show up in one.
+
+use std::{
+    env,
+    io::{BufRead, BufReader},
+    os::unix::process::CommandExt,
+    process::{Command, Stdio},
+    sync::{mpsc, OnceLock},
+    thread,
+};
+
+static ADDRESS: OnceLock<Option<String>> = OnceLock::new();
+
+/// Address of a bus shared by every test of the run, tests connect to it explicitly.
+/// Gives `None` when dbus-daemon isn't installed and the test should be skipped,
+/// on CI that fails the test instead, so the D-Bus code can't go untested there.
+pub fn private_session_bus() -> Option<&'static str> {
+    let address = ADDRESS.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+
+        // The daemon is killed once this thread goes away, which only happens when the test run ends
+        thread::spawn(move || {
+            let mut command = Command::new("dbus-daemon");
+            command.args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null());
+
+            unsafe {
+                command.pre_exec(|| {
+                    libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+                    Ok(())
+                });
+            }
+
+            let Ok(mut daemon) = command.spawn() else {
+                let _ = tx.send(None);
+                return;
+            };
+
+            let mut address = String::new();
+            let stdout = daemon.stdout.take().expect("stdout is piped");
+            let _ = BufReader::new(stdout).read_line(&mut address);
+            let _ = tx.send(Some(address.trim().to_string()).filter(|address| !address.is_empty()));
+
+            let _ = daemon.wait();
+        });
+
+        rx.recv().ok().flatten()
+    });
+
+    if address.is_none() {
+        assert!(env::var_os("CI").is_none(), "dbus-daemon is required to run the D-Bus tests on CI");
+        eprintln!("dbus-daemon is not available, skipping");
+    }
+
+    address.as_deref()
+}
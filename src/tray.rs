@@ -1,28 +1,41 @@
-use std::{
-    ffi::OsStr, os::windows::ffi::OsStrExt, sync::{mpsc, Arc}
-};
+use std::{collections::HashSet, sync::{mpsc, Arc}};
+#[cfg(windows)]
+use std::{ffi::OsStr, os::windows::ffi::OsStrExt};
+use chrono::Utc;
 use tokio::sync::Mutex;
 use anyhow::{Result, Context};
+#[cfg(windows)]
 use windows_sys::Win32::UI::{
     HiDpi::{SetProcessDpiAwareness, PROCESS_PER_MONITOR_DPI_AWARE},
     Shell::SetCurrentProcessExplicitAppUserModelID,
 };
 
-use crate::tray_body::TrayBody;
+use crate::tray_body::{TrayBackend, TrayBody};
+use crate::claims::Claims;
 use crate::{config::Config, games::Games};
+use crate::menu::{MenuInput, MenuModel};
 use crate::resource::ResourceIcon;
 
+const TRAY_TITLE: &str = "FreeTrayGames";
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Message {
     Quit,
     OpenGame(usize),
     OpenProjectHomepage,
     Refresh,
     ToggleNotifications,
+    CheckReminders,
+    MarkClaimed(usize),
+    UnmarkClaimed(usize),
+    OpenClaimHistory,
 }
 
 pub struct Tray {
     current: Arc<Mutex<Option<TrayBody>>>,
+    labels: Arc<Mutex<Vec<String>>>,
     games: Games,
+    claims: Claims,
     tx: mpsc::SyncSender<Message>,
     config: Config,
 }
@@ -30,6 +43,7 @@ pub struct Tray {
 impl Tray {
     pub async fn new(
         games: Games,
+        claims: Claims,
         config: Config,
     ) -> (Self, mpsc::Receiver<Message>) {
         let (tx, rx) = mpsc::sync_channel(1);
@@ -37,7 +51,9 @@ impl Tray {
 
         let new_tray = Tray {
             games,
+            claims,
             current,
+            labels: Arc::new(Mutex::new(Vec::new())),
             tx,
             config,
         };
@@ -56,88 +72,68 @@ impl Tray {
 
     pub async fn rebuild_tray(&self) -> Result<()> {
         let mut current = self.current.lock().await;
-        let new_tray = self.build_tray().await.context("Failed to build tray")?;
-        *current = Some(new_tray);
+        let model = self.build_model().await;
+
+        match current.as_mut() {
+            Some(tray) => {
+                tray.update_model(TRAY_TITLE, &ResourceIcon::Main, &model, &self.tx)
+                    .context("Failed to update tray")?;
+            }
+            None => {
+                let tray = TrayBody::from_model(TRAY_TITLE, &ResourceIcon::Main, &model, &self.tx)
+                    .context("Failed to build tray")?;
+                *current = Some(tray);
+            }
+        }
+
         Ok(())
     }
 
-    async fn build_tray(&self) -> Result<TrayBody> {
-        let mut tray = TrayBody::new("FreeTrayGames", &ResourceIcon::Main).context("Failed to create tray instance")?;
-        let tx = self.tx.clone();
+    /// Rebuilds the tray only when a countdown next to one of the offers has changed.
+    pub async fn refresh_countdowns(&self) -> Result<()> {
+        let now = Utc::now();
+        let labels: Vec<String> = self.games.get_all().await
+            .iter()
+            .map(|game| MenuModel::get_game_label(game, now))
+            .collect();
+
+        if *self.labels.lock().await != labels {
+            self.rebuild_tray().await?;
+        }
+
+        Ok(())
+    }
 
+    async fn build_model(&self) -> MenuModel {
         let games = self.games.get_all().await;
+        let now = Utc::now();
 
-        match (games.len(), self.games.get_error_reason().await) {
-            (_, Some(error_reason)) => {
-                tray.add_label(format!("Failed to load: {}", error_reason).as_str())
-                    .context("Failed to add error reason label")?;
-            }
-            (0, None) => {
-                tray.add_label("No active giveaways")
-                    .context("Failed to add no active giveaways menu item")?;
-            }
-            (_, None)  => {
-                for game in &games {
-                    let title = game.title.clone();
-                    let id = game.id;
-                    let item_open_tx = tx.clone();
-
-                    let icon = match game.platform.as_str() {
-                        "steam" => Some(&ResourceIcon::BrandSteam),
-                        "epic" => Some(&ResourceIcon::BrandEpic),
-                        "gog" => Some(&ResourceIcon::BrandGog),
-                        _ => None,
-                    };
-
-                    tray.add_menu_item(&title, move || {
-                        let _ = item_open_tx.send(Message::OpenGame(id));
-                    }, if let Some(
-                        icon,
-                    ) = icon {
-                        Some(icon)
-                    } else {
-                        None
-                    }).context("Failed to add game menu item")?;
-                }
+        let mut claimed = HashSet::new();
+
+        for game in &games {
+            if self.claims.is_claimed(game.id).await {
+                claimed.insert(game.id);
             }
         }
 
-        tray.add_separator().context("Failed to add separator")?;
-
-        let refresh_tx = tx.clone();
-        tray.add_menu_item("Refresh", move || {
-            let _ = refresh_tx.send(Message::Refresh);
-        }, Some(&ResourceIcon::Refresh)).context("Failed to add refresh menu item")?;
-
-        let open_project_homepage_tx = tx.clone();
-        tray.add_menu_item("Homepage", move || {
-            let _ = open_project_homepage_tx.send(Message::OpenProjectHomepage);
-        }, Some(&ResourceIcon::BrandGithub))
-            .context("Failed to add project homepage menu item")?;
-
-        let notifications_tx = tx.clone();
-        let is_notifications_enabled = self.config.is_notifications_enabled().await;
-        let notifications_icon = if is_notifications_enabled {
-            &ResourceIcon::NotificationsEnabled
-        } else {
-            &ResourceIcon::NotificationsDisabled
-        };
-        tray.add_menu_item("Toggle Notifications", move || {
-            let _ = notifications_tx.send(Message::ToggleNotifications);
-        }, Some(&notifications_icon))
-            .context("Failed to add toggle notifications menu item")?;
-
-        tray.add_separator().context("Failed to add separator")?;
+        let model = MenuModel::build(&MenuInput {
+            games: &games,
+            claimed: &claimed,
+            error_reasons: &self.games.get_error_reasons().await,
+            stale_since: self.games.get_stale_since().await,
+            claimed_display: self.config.get_claimed_display().await,
+            is_notifications_enabled: self.config.is_notifications_enabled().await,
+            now,
+        });
 
-        let quit_tx = tx.clone();
-        tray.add_menu_item("Quit", move || {
-            let _ = quit_tx.send(Message::Quit);
-        }, None)
-            .context("Failed to add quit menu item")?;
+        *self.labels.lock().await = games.iter()
+            .map(|game| MenuModel::get_game_label(game, now))
+            .collect();
 
-        Ok(tray)
+        model
     }
 
+    #[cfg(windows)]
     pub fn make_tray_nice() {
         let app_id = OsStr::new(Config::get_app_id().as_str())
             .encode_wide()
@@ -149,4 +145,7 @@ impl Tray {
             SetProcessDpiAwareness(PROCESS_PER_MONITOR_DPI_AWARE);
         }
     }
-}
\ No newline at end of file
+
+    #[cfg(not(windows))]
+    pub fn make_tray_nice() {}
+}
@@ -0,0 +1,47 @@
+use std::io::Cursor;
+use anyhow::{Context, Result};
+use ico::{IconDir, IconImage};
+
+use crate::resource::ResourceIcon;
+
+/// `(width, height, ARGB32 data in network byte order)` as expected by `IconPixmap`.
+pub(crate) type Pixmap = (i32, i32, Vec<u8>);
+
+fn decode_images(icon: &ResourceIcon) -> Result<Vec<IconImage>> {
+    let dir = IconDir::read(Cursor::new(icon.get_icon_bytes()))
+        .with_context(|| format!("Failed to read icon {}", icon.get_icon_path()))?;
+
+    dir.entries().iter()
+        .map(|entry| entry.decode().context("Failed to decode icon"))
+        .collect()
+}
+
+pub(crate) fn icon_to_pixmaps(icon: &ResourceIcon) -> Result<Vec<Pixmap>> {
+    let pixmaps = decode_images(icon)?
+        .iter()
+        .map(|image| {
+            let argb = image.rgba_data()
+                .as_chunks::<4>().0
+                .iter()
+                .flat_map(|[r, g, b, a]| [*a, *r, *g, *b])
+                .collect();
+
+            (image.width() as i32, image.height() as i32, argb)
+        })
+        .collect();
+
+    Ok(pixmaps)
+}
+
+/// Encodes the image closest to 16x16 as PNG, which is what menus accept as `icon-data`.
+pub(crate) fn icon_to_png(icon: &ResourceIcon) -> Result<Vec<u8>> {
+    let image = decode_images(icon)?
+        .into_iter()
+        .min_by_key(|image| image.width().abs_diff(16))
+        .context("Icon has no images")?;
+
+    let mut png = Vec::new();
+    image.write_png(&mut png).context("Failed to encode icon")?;
+
+    Ok(png)
+}
@@ -0,0 +1,180 @@
+use std::sync::{Arc, Mutex};
+use zbus::{fdo, interface, zvariant::{ObjectPath, Value}};
+
+use super::icons::Pixmap;
+use super::structs::{Layout, MenuState};
+use super::MENU_PATH;
+
+/// `org.kde.StatusNotifierItem`, the icon itself. Clicking it always opens the menu.
+pub(crate) struct StatusNotifierItem {
+    pub id: String,
+    pub title: String,
+    pub icon: Vec<Pixmap>,
+}
+
+#[interface(name = "org.kde.StatusNotifierItem")]
+impl StatusNotifierItem {
+    fn activate(&self, _x: i32, _y: i32) {}
+
+    fn secondary_activate(&self, _x: i32, _y: i32) {}
+
+    fn context_menu(&self, _x: i32, _y: i32) {}
+
+    fn scroll(&self, _delta: i32, _orientation: &str) {}
+
+    #[zbus(property)]
+    fn category(&self) -> &str {
+        "ApplicationStatus"
+    }
+
+    #[zbus(property)]
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[zbus(property)]
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    #[zbus(property)]
+    fn status(&self) -> &str {
+        "Active"
+    }
+
+    #[zbus(property)]
+    fn window_id(&self) -> i32 {
+        0
+    }
+
+    #[zbus(property)]
+    fn icon_name(&self) -> &str {
+        ""
+    }
+
+    #[zbus(property)]
+    fn icon_pixmap(&self) -> Vec<Pixmap> {
+        self.icon.clone()
+    }
+
+    #[zbus(property)]
+    fn tool_tip(&self) -> (String, Vec<Pixmap>, String, String) {
+        (String::new(), Vec::new(), self.title.clone(), String::new())
+    }
+
+    #[zbus(property)]
+    fn item_is_menu(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn menu(&self) -> ObjectPath<'static> {
+        ObjectPath::from_static_str_unchecked(MENU_PATH)
+    }
+}
+
+/// `com.canonical.dbusmenu`, the context menu shared with `TrayBody`.
+pub(crate) struct DbusMenu {
+    pub menu: Arc<Mutex<MenuState>>,
+}
+
+impl DbusMenu {
+    fn with_menu<T>(&self, f: impl FnOnce(&MenuState) -> T) -> fdo::Result<T> {
+        let menu = self.menu.lock()
+            .map_err(|_| fdo::Error::Failed("Menu is unavailable".into()))?;
+
+        Ok(f(&menu))
+    }
+}
+
+#[interface(name = "com.canonical.dbusmenu")]
+impl DbusMenu {
+    fn get_layout(
+        &self,
+        parent_id: i32,
+        recursion_depth: i32,
+        property_names: Vec<String>,
+    ) -> fdo::Result<(u32, Layout)> {
+        self.with_menu(|menu| {
+            menu.layout(parent_id, recursion_depth, &property_names)
+                .map(|layout| (menu.revision, layout))
+                .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown menu item {}", parent_id)))
+        })?
+    }
+
+    fn get_group_properties(
+        &self,
+        ids: Vec<i32>,
+        property_names: Vec<String>,
+    ) -> fdo::Result<Vec<(i32, std::collections::HashMap<String, Value<'static>>)>> {
+        self.with_menu(|menu| {
+            ids.iter()
+                .filter_map(|id| menu.get(*id).map(|item| (*id, item.properties(&property_names))))
+                .collect()
+        })
+    }
+
+    fn get_property(&self, id: i32, name: String) -> fdo::Result<Value<'static>> {
+        self.with_menu(|menu| {
+            menu.get(id)
+                .and_then(|item| item.properties(std::slice::from_ref(&name)).remove(&name))
+                .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown property {} of {}", name, id)))
+        })?
+    }
+
+    fn event(&self, id: i32, event_id: &str, _data: Value<'_>, _timestamp: u32) {
+        if event_id != "clicked" {
+            return;
+        }
+
+        let callback = self.with_menu(|menu| menu.get(id).and_then(|item| item.callback.clone()));
+
+        // Called without the lock, the callback may block on a full channel while the menu gets replaced
+        if let Ok(Some(f)) = callback {
+            f();
+        }
+    }
+
+    fn event_group(&self, events: Vec<(i32, String, Value<'_>, u32)>) -> Vec<i32> {
+        let mut id_errors = Vec::new();
+
+        for (id, event_id, data, timestamp) in events {
+            if self.with_menu(|menu| menu.get(id).is_none()).unwrap_or(true) {
+                id_errors.push(id);
+                continue;
+            }
+
+            self.event(id, &event_id, data, timestamp);
+        }
+
+        id_errors
+    }
+
+    fn about_to_show(&self, _id: i32) -> bool {
+        false
+    }
+
+    fn about_to_show_group(&self, _ids: Vec<i32>) -> (Vec<i32>, Vec<i32>) {
+        (Vec::new(), Vec::new())
+    }
+
+    #[zbus(property)]
+    fn version(&self) -> u32 {
+        3
+    }
+
+    #[zbus(property)]
+    fn text_direction(&self) -> &str {
+        "ltr"
+    }
+
+    #[zbus(property)]
+    fn status(&self) -> &str {
+        "normal"
+    }
+
+    #[zbus(property)]
+    fn icon_theme_path(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
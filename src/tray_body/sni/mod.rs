@@ -0,0 +1,297 @@
+//! StatusNotifierItem with a com.canonical.dbusmenu menu, as understood by KDE, most wlroots
+//! bars and GNOME with the AppIndicator extension. Uses the session bus from
+//! DBUS_SESSION_BUS_ADDRESS, so it can be pointed at a private dbus-daemon as well.
+
+mod icons;
+mod interfaces;
+mod structs;
+
+use std::{
+    mem,
+    process,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        mpsc::SyncSender,
+        Arc, Mutex,
+    },
+};
+use anyhow::{anyhow, Context, Result};
+use zbus::{blocking::{connection, Connection}, names::BusName};
+
+use icons::*;
+use interfaces::*;
+use structs::*;
+
+use super::TrayBackend;
+use crate::{menu::MenuModel, resource::ResourceIcon, tray::Message};
+
+pub(crate) const ITEM_PATH: &str = "/StatusNotifierItem";
+pub(crate) const MENU_PATH: &str = "/MenuBar";
+
+const WATCHER_NAME: &str = "org.kde.StatusNotifierWatcher";
+const WATCHER_PATH: &str = "/StatusNotifierWatcher";
+const MENU_INTERFACE: &str = "com.canonical.dbusmenu";
+
+/// Keeps item names unique when a process creates more than one tray.
+static INSTANCE_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// One item registered with the watcher for the whole life of the tray. Menu changes are
+/// staged and then swapped in at once, so hosts never see a half-built menu.
+pub struct TrayBody {
+    menu: Arc<Mutex<MenuState>>,
+    staged: MenuState,
+    connection: Connection,
+}
+
+impl TrayBody {
+    /// Registers the item on the bus the builder connects to.
+    fn on_bus(builder: connection::Builder, title: &str, icon: &ResourceIcon) -> Result<Self> {
+        let menu = Arc::new(Mutex::new(MenuState::new()));
+
+        let item = StatusNotifierItem {
+            id: title.to_string(),
+            title: title.to_string(),
+            icon: icon_to_pixmaps(icon).context("Failed to load tray icon")?,
+        };
+
+        let name = format!(
+            "org.kde.StatusNotifierItem-{}-{}",
+            process::id(),
+            INSTANCE_COUNTER.fetch_add(1, Ordering::Relaxed),
+        );
+
+        let connection = builder.name(name.as_str())
+            .and_then(|b| b.serve_at(ITEM_PATH, item))
+            .and_then(|b| b.serve_at(MENU_PATH, DbusMenu { menu: Arc::clone(&menu) }))
+            .and_then(|b| b.build())
+            .context("Failed to connect to the session bus")?;
+
+        connection.call_method(
+            Some(WATCHER_NAME),
+            WATCHER_PATH,
+            Some(WATCHER_NAME),
+            "RegisterStatusNotifierItem",
+            &name,
+        ).context("Failed to register tray icon, is a StatusNotifierItem host running?")?;
+
+        Ok(Self {
+            menu,
+            staged: MenuState::new(),
+            connection,
+        })
+    }
+
+    fn push_item(&mut self, parent: Option<SubMenu>, item: MenuItem) -> Result<u32> {
+        let id = self.staged.push(parent.map(|p| p.0).unwrap_or(0), item);
+        Ok(id as u32)
+    }
+
+    fn load_menu_icon(icon: Option<&ResourceIcon>) -> Result<Option<Vec<u8>>> {
+        icon.map(icon_to_png).transpose()
+    }
+}
+
+impl TrayBackend for TrayBody {
+    type SubMenu = SubMenu;
+
+    fn new(title: &str, icon: &ResourceIcon) -> Result<Self> {
+        let builder = connection::Builder::session().context("Failed to connect to the session bus")?;
+        Self::on_bus(builder, title, icon)
+    }
+
+    fn add_label(&mut self, label: &str) -> Result<()> {
+        self.push_item(None, MenuItem::new(MenuItemKind::Label, label))
+            .context("Failed to add label")?;
+        Ok(())
+    }
+
+    fn add_menu_item_to<F>(
+        &mut self,
+        parent: Option<SubMenu>,
+        label: &str,
+        cb: F,
+        icon: Option<&ResourceIcon>,
+        checked: bool,
+    ) -> Result<u32>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let mut item = MenuItem::new(MenuItemKind::Standard, label);
+        item.icon = Self::load_menu_icon(icon)?;
+        item.callback = Some(Arc::new(cb));
+        item.checked = checked.then_some(true);
+
+        self.push_item(parent, item)
+    }
+
+    fn add_submenu(&mut self, label: &str, icon: Option<&ResourceIcon>) -> Result<SubMenu> {
+        let mut item = MenuItem::new(MenuItemKind::Submenu, label);
+        item.icon = Self::load_menu_icon(icon)?;
+
+        let id = self.push_item(None, item).context("Failed to add submenu")?;
+        Ok(SubMenu(id as i32))
+    }
+
+    fn add_separator(&mut self) -> Result<()> {
+        self.push_item(None, MenuItem::new(MenuItemKind::Separator, ""))
+            .context("Failed to add separator")?;
+        Ok(())
+    }
+
+    fn finish_menu(&mut self) -> Result<()> {
+        let mut staged = mem::replace(&mut self.staged, MenuState::new());
+
+        let revision = {
+            let mut menu = self.menu.lock().map_err(|_| anyhow!("Menu is poisoned"))?;
+            // Hosts only refetch the layout when the revision moves forward
+            staged.revision = menu.revision + 1;
+            *menu = staged;
+            menu.revision
+        };
+
+        self.connection.emit_signal(
+            None::<BusName>,
+            MENU_PATH,
+            MENU_INTERFACE,
+            "LayoutUpdated",
+            &(revision, 0i32),
+        ).context("Failed to announce menu update")?;
+
+        Ok(())
+    }
+
+    /// Replaces the menu of the existing item, re-registering would make the icon jump around the panel.
+    fn update_model(&mut self, _title: &str, _icon: &ResourceIcon, model: &MenuModel, tx: &SyncSender<Message>) -> Result<()> {
+        self.staged = MenuState::new();
+        self.add_model(model, tx)?;
+        self.finish_menu()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::mpsc::sync_channel, time::Duration};
+    use zbus::{
+        blocking::Proxy,
+        interface,
+        zvariant::{OwnedValue, Value},
+    };
+
+    use super::*;
+    use crate::{menu::{MenuEntry, MenuItem as ModelItem}, test_bus::private_session_bus};
+
+    type OwnedLayout = (i32, HashMap<String, OwnedValue>, Vec<OwnedValue>);
+
+    /// Stands in for the panel, remembers which items asked to be shown.
+    struct Watcher {
+        registered: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[interface(name = "org.kde.StatusNotifierWatcher")]
+    impl Watcher {
+        fn register_status_notifier_item(&self, service: &str) {
+            self.registered.lock().unwrap().push(service.to_string());
+        }
+    }
+
+    fn item(label: &str, action: Message, checked: bool) -> ModelItem {
+        ModelItem {
+            label: label.to_string(),
+            icon: None,
+            action,
+            checked,
+        }
+    }
+
+    fn get_layout(menu: &Proxy) -> (u32, Vec<OwnedLayout>) {
+        let (revision, (_, _, children)): (u32, OwnedLayout) = menu
+            .call("GetLayout", &(0i32, -1i32, Vec::<String>::new()))
+            .unwrap();
+
+        let children = children.into_iter()
+            .map(|child| OwnedLayout::try_from(child).unwrap())
+            .collect();
+
+        (revision, children)
+    }
+
+    fn get_label((_, properties, _): &OwnedLayout) -> String {
+        match (properties.get("label"), properties.get("type")) {
+            (Some(label), _) => String::try_from(label.try_clone().unwrap()).unwrap(),
+            (None, Some(_)) => "---".to_string(),
+            (None, None) => String::new(),
+        }
+    }
+
+    #[test]
+    fn registers_one_item_and_replaces_its_menu() {
+        let Some(address) = private_session_bus() else {
+            return;
+        };
+
+        let registered = Arc::new(Mutex::new(Vec::new()));
+        let _watcher = connection::Builder::address(address)
+            .and_then(|b| b.name(WATCHER_NAME))
+            .and_then(|b| b.serve_at(WATCHER_PATH, Watcher { registered: Arc::clone(&registered) }))
+            .and_then(|b| b.build())
+            .unwrap();
+
+        let model = MenuModel {
+            entries: vec![
+                MenuEntry::Label("No active giveaways".to_string()),
+                MenuEntry::Item(ModelItem {
+                    icon: Some(ResourceIcon::Refresh),
+                    ..item("Refresh", Message::Refresh, false)
+                }),
+                MenuEntry::Separator,
+                MenuEntry::Submenu {
+                    label: "Mark as Claimed".to_string(),
+                    icon: None,
+                    items: vec![item("Hollow_Lantern", Message::UnmarkClaimed(1), true)],
+                },
+            ],
+        };
+
+        let (tx, rx) = sync_channel(4);
+        let mut tray = TrayBody::on_bus(connection::Builder::address(address).unwrap(), "FreeTrayGames", &ResourceIcon::Main).unwrap();
+        tray.add_model(&model, &tx).unwrap();
+        tray.finish_menu().unwrap();
+
+        let name = registered.lock().unwrap().first().cloned().expect("item wasn't registered");
+        let client = connection::Builder::address(address).and_then(|b| b.build()).unwrap();
+
+        let item_proxy = Proxy::new(&client, name.as_str(), ITEM_PATH, "org.kde.StatusNotifierItem").unwrap();
+        assert_eq!(item_proxy.get_property::<String>("Title").unwrap(), "FreeTrayGames");
+        assert!(item_proxy.get_property::<bool>("ItemIsMenu").unwrap());
+
+        let menu = Proxy::new(&client, name.as_str(), MENU_PATH, MENU_INTERFACE).unwrap();
+        let (revision, children) = get_layout(&menu);
+        let labels: Vec<String> = children.iter().map(get_label).collect();
+
+        assert_eq!(labels, ["No active giveaways", "Refresh", "---", "Mark as Claimed"]);
+        assert!(children[1].1.contains_key("icon-data"));
+
+        let claimed = OwnedLayout::try_from(children[3].2[0].try_clone().unwrap()).unwrap();
+        assert_eq!(get_label(&claimed), "Hollow__Lantern");
+        assert_eq!(i32::try_from(&claimed.1["toggle-state"]).unwrap(), 1);
+
+        let _: () = menu.call("Event", &(children[1].0, "clicked", Value::from(0i32), 0u32)).unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), Message::Refresh);
+
+        let mut updates = menu.receive_signal("LayoutUpdated").unwrap();
+
+        let model = MenuModel {
+            entries: vec![MenuEntry::Label("Everything is claimed".to_string())],
+        };
+        tray.update_model("FreeTrayGames", &ResourceIcon::Main, &model, &tx).unwrap();
+
+        let (updated_revision, _): (u32, i32) = updates.next().unwrap().body().deserialize().unwrap();
+        assert!(updated_revision > revision);
+
+        let (current_revision, children) = get_layout(&menu);
+        assert_eq!(current_revision, updated_revision);
+        assert_eq!(children.iter().map(get_label).collect::<Vec<_>>(), ["Everything is claimed"]);
+
+        assert_eq!(registered.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,133 @@
+use std::{collections::HashMap, sync::Arc};
+use zbus::zvariant::Value;
+
+/// Shared, so a click can take the callback out of the menu and run it after unlocking.
+pub(crate) type CallBackEntry = Option<Arc<dyn Fn() + Send + Sync + 'static>>;
+
+/// `(id, properties, children)` as expected by `com.canonical.dbusmenu`.
+pub(crate) type Layout = (i32, HashMap<String, Value<'static>>, Vec<Value<'static>>);
+
+pub(crate) enum MenuItemKind {
+    Root,
+    Label,
+    Standard,
+    Submenu,
+    Separator,
+}
+
+pub(crate) struct MenuItem {
+    pub kind: MenuItemKind,
+    pub label: String,
+    pub icon: Option<Vec<u8>>,
+    pub checked: Option<bool>,
+    pub children: Vec<i32>,
+    pub callback: CallBackEntry,
+}
+
+impl MenuItem {
+    pub fn new(kind: MenuItemKind, label: &str) -> Self {
+        Self {
+            kind,
+            label: label.to_string(),
+            icon: None,
+            checked: None,
+            children: Vec::new(),
+            callback: None,
+        }
+    }
+
+    /// Properties of the item, limited to the requested names. Empty `names` means all of them.
+    pub fn properties(&self, names: &[String]) -> HashMap<String, Value<'static>> {
+        let mut properties: HashMap<String, Value<'static>> = HashMap::new();
+
+        match self.kind {
+            MenuItemKind::Root => {
+                properties.insert("children-display".into(), "submenu".into());
+            }
+            MenuItemKind::Separator => {
+                properties.insert("type".into(), "separator".into());
+            }
+            MenuItemKind::Label => {
+                properties.insert("label".into(), escape_label(&self.label).into());
+                properties.insert("enabled".into(), false.into());
+            }
+            MenuItemKind::Standard => {
+                properties.insert("label".into(), escape_label(&self.label).into());
+            }
+            MenuItemKind::Submenu => {
+                properties.insert("label".into(), escape_label(&self.label).into());
+                properties.insert("children-display".into(), "submenu".into());
+            }
+        }
+
+        if let Some(checked) = self.checked {
+            properties.insert("toggle-type".into(), "checkmark".into());
+            properties.insert("toggle-state".into(), i32::from(checked).into());
+        }
+
+        if let Some(icon) = &self.icon {
+            properties.insert("icon-data".into(), icon.clone().into());
+        }
+
+        if !names.is_empty() {
+            properties.retain(|name, _| names.contains(name));
+        }
+
+        properties
+    }
+}
+
+pub(crate) struct MenuState {
+    pub revision: u32,
+    pub items: Vec<MenuItem>,
+}
+
+impl MenuState {
+    pub fn new() -> Self {
+        Self {
+            revision: 0,
+            items: vec![MenuItem::new(MenuItemKind::Root, "")],
+        }
+    }
+
+    pub fn get(&self, id: i32) -> Option<&MenuItem> {
+        usize::try_from(id).ok().and_then(|id| self.items.get(id))
+    }
+
+    /// Appends the item at the end of the given parent and returns its id.
+    pub fn push(&mut self, parent: i32, item: MenuItem) -> i32 {
+        let id = self.items.len() as i32;
+        self.items.push(item);
+
+        if let Some(parent) = self.items.get_mut(parent as usize) {
+            parent.children.push(id);
+        }
+
+        self.revision += 1;
+        id
+    }
+
+    /// Builds the layout of the given item, going `depth` levels down (`-1` means everything).
+    pub fn layout(&self, id: i32, depth: i32, names: &[String]) -> Option<Layout> {
+        let item = self.get(id)?;
+
+        let children = if depth == 0 {
+            Vec::new()
+        } else {
+            item.children.iter()
+                .filter_map(|child| self.layout(*child, depth - 1, names))
+                .map(Value::from)
+                .collect()
+        };
+
+        Some((id, item.properties(names), children))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SubMenu(pub(crate) i32);
+
+/// Underscores mark mnemonics in dbusmenu labels, titles need them doubled to show up as-is.
+fn escape_label(label: &str) -> String {
+    label.replace('_', "__")
+}
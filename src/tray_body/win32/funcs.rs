@@ -12,7 +12,7 @@ use windows_sys::Win32::{
             MIM_APPLYTOSUBMENUS, MIM_STYLE, MNS_NOTIFYBYPOS, MSG, TPM_BOTTOMALIGN, TPM_LEFTALIGN,
             TPM_LEFTBUTTON, WM_LBUTTONUP, WM_MENUCOMMAND, WM_QUIT, WM_RBUTTONUP, WM_USER,
             WNDCLASSW, WS_OVERLAPPEDWINDOW, WM_CREATE, HICON, IDI_APPLICATION, LoadIconW,
-            RegisterWindowMessageW,
+            RegisterWindowMessageW, HMENU,
         },
     },
 };
@@ -43,7 +43,10 @@ pub(crate) unsafe extern "system" fn window_proc(
             let stash = stash.borrow();
             let stash = stash.as_ref();
             if let Some(stash) = stash {
-                let menu_id = GetMenuItemID(stash.info.hmenu, w_param as i32) as i32;
+                // With MNS_NOTIFYBYPOS the menu owning the clicked item comes in lParam,
+                // which matters once items live in submenus
+                let hmenu = if l_param != 0 { l_param as HMENU } else { stash.info.hmenu };
+                let menu_id = GetMenuItemID(hmenu, w_param as i32) as i32;
                 if menu_id != -1 {
                     stash.tx.send(WindowsTrayEvent(menu_id as u32)).ok();
                 }
@@ -196,6 +199,24 @@ pub(crate) unsafe fn init_window() -> Result<WindowInfo> {
     })
 }
 
+pub(crate) unsafe fn create_submenu() -> Result<HMENU> {
+    let mut info = unsafe { mem::zeroed::<MENUINFO>() };
+    info.cbSize = mem::size_of::<MENUINFO>() as u32;
+    info.fMask = MIM_STYLE;
+    info.dwStyle = MNS_NOTIFYBYPOS;
+    let hmenu = CreatePopupMenu();
+
+    if hmenu == 0 {
+        bail!("Failed at creating submenu");
+    }
+
+    if SetMenuInfo(hmenu, &info) == 0 {
+        bail!("Failed at setting up submenu");
+    }
+
+    Ok(hmenu)
+}
+
 pub(crate) unsafe fn run_loop() {
     // Run message loop
     let mut msg = unsafe { mem::zeroed::<MSG>() };
@@ -22,3 +22,6 @@ pub(crate) struct WindowsLoopData {
 }
 
 pub(crate) struct WindowsTrayEvent(pub(crate) u32);
+
+#[derive(Clone, Copy)]
+pub struct SubMenu(pub(crate) HMENU);